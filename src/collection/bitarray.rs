@@ -73,6 +73,67 @@ impl BitArray {
         return self.bits.len() - self.bits.iter().fold(0,|a,b|a+b.count_ones() as usize);
     }
 
+    /// Finds the first one at or after the specified index.
+    pub fn next_set_bit(&self, from: usize) -> Option<usize> {
+        self.next_bit(from, 0)
+    }
+
+    /// Finds the first zero at or after the specified index.
+    pub fn next_unset_bit(&self, from: usize) -> Option<usize> {
+        self.next_bit(from, !0)
+    }
+
+    /// Finds the last one at or before the specified index.
+    pub fn prev_set_bit(&self, at: usize) -> Option<usize> {
+        self.prev_bit(at, 0)
+    }
+
+    /// Finds the last zero at or before the specified index.
+    pub fn prev_unset_bit(&self, at: usize) -> Option<usize> {
+        self.prev_bit(at, !0)
+    }
+
+    // Finds the first one in the array xor-ed with flip, at or after from.
+    fn next_bit(&self, from: usize, flip: u128) -> Option<usize> {
+        if from >= self.num_bits {
+            return None;
+        }
+        let mut q = from/Self::BITS_PER_UNIT;
+        let mut b = (self.bits[q] ^ flip) & (!0 << (from%Self::BITS_PER_UNIT));
+        while b == 0 {
+            q += 1;
+            if q == self.arr_size {
+                return None;
+            }
+            b = self.bits[q] ^ flip;
+        }
+        let idx = q*Self::BITS_PER_UNIT + b.trailing_zeros() as usize;
+        if idx < self.num_bits { Some(idx) } else { None }
+    }
+
+    // Finds the last one in the array xor-ed with flip, at or before at.
+    fn prev_bit(&self, at: usize, flip: u128) -> Option<usize> {
+        if self.num_bits == 0 {
+            return None;
+        }
+        let at = at.min(self.num_bits - 1);
+        let mut q = at/Self::BITS_PER_UNIT;
+        let mut b = (self.bits[q] ^ flip) & (!0 >> (Self::BITS_PER_UNIT - 1 - at%Self::BITS_PER_UNIT));
+        while b == 0 {
+            if q == 0 {
+                return None;
+            }
+            q -= 1;
+            b = self.bits[q] ^ flip;
+        }
+        Some(q*Self::BITS_PER_UNIT + Self::BITS_PER_UNIT - 1 - b.leading_zeros() as usize)
+    }
+
+    /// Iterates over the indices of ones in increasing order.
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes { barr: self, next: 0 }
+    }
+
     fn panic_if_out_of_input_range(num_bits: usize, at:usize) {
         if at > num_bits {
             panic!("Index {} out of range: {}.", at, num_bits);
@@ -242,6 +303,37 @@ impl ShrAssign<usize> for BitArray {
     }
 }
 
+/// An iterator over the indices of ones in a BitArray.
+pub struct IterOnes<'a> {
+    barr: &'a BitArray,
+    next: usize,
+}
+
+impl Iterator for IterOnes<'_> {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.barr.next_set_bit(self.next)?;
+        self.next = idx + 1;
+        Some(idx)
+    }
+}
+
+// Finds the index of the k-th one in a unit, which must exist.
+fn select_in_unit(mut unit: u128, mut k: usize) -> usize {
+    let mut pos = 0;
+    let mut width = BitArray::BITS_PER_UNIT / 2;
+    while width > 0 {
+        let low = (unit & ((1u128 << width) - 1)).count_ones() as usize;
+        if k >= low {
+            k -= low;
+            unit >>= width;
+            pos += width;
+        }
+        width >>= 1;
+    }
+    pos
+}
+
 impl From<&[bool]> for BitArray {
     fn from(bits: &[bool]) -> Self {
         let mut new = Self::new(bits.len());
@@ -266,10 +358,155 @@ impl <const N:usize> From<&[bool; N]> for BitArray {
     }
 }
 
+/// An immutable bit array with a directory of the number of ones preceding
+/// each u128 unit, answering rank queries in O(1) and select queries in
+/// O(log n). This is the building block of succinct data structures such as
+/// the wavelet matrix.
+#[derive(Debug,Clone)]
+pub struct RankSelect {
+    bits: BitArray,
+    ones_before: Vec<usize>,
+}
+
+impl From<BitArray> for RankSelect {
+    fn from(bits: BitArray) -> Self {
+        let mut ones_before = Vec::with_capacity(bits.arr_size+1);
+        ones_before.push(0);
+        for (i, b) in bits.bits.iter().enumerate() {
+            ones_before.push(ones_before[i] + b.count_ones() as usize);
+        }
+        Self { bits, ones_before }
+    }
+}
+
+impl RankSelect {
+    /// Gets the underlying bit array.
+    pub fn bits(&self) -> &BitArray {
+        &self.bits
+    }
+
+    /// Gets the length of bits.
+    pub fn len(&self) -> usize {
+        self.bits.num_bits
+    }
+
+    /// Returns true if there are no bits.
+    pub fn is_empty(&self) -> bool {
+        self.bits.num_bits == 0
+    }
+
+    /// Counts the number of ones before the specified index, i.e., in [0, at).
+    pub fn rank1(&self, at: usize) -> usize {
+        self.bits.panic_if_out_of_range(at);
+        let (q, m) = (at / BitArray::BITS_PER_UNIT, at % BitArray::BITS_PER_UNIT);
+        let below = (1u128 << m).wrapping_sub(1);
+        self.ones_before[q] + (self.bits.bits[q] & below).count_ones() as usize
+    }
+
+    /// Counts the number of zeros before the specified index, i.e., in [0, at).
+    pub fn rank0(&self, at: usize) -> usize {
+        at - self.rank1(at)
+    }
+
+    /// Finds the index of the k-th one (0-indexed), if any.
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones_before[self.bits.arr_size] {
+            return None;
+        }
+        // the last unit lo such that ones_before[lo] <= k
+        let (mut lo, mut hi) = (0, self.bits.arr_size);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if self.ones_before[mid] <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(lo * BitArray::BITS_PER_UNIT + select_in_unit(self.bits.bits[lo], k - self.ones_before[lo]))
+    }
+
+    /// Finds the index of the k-th zero (0-indexed), if any.
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.len() - self.ones_before[self.bits.arr_size] {
+            return None;
+        }
+        let zeros_before = |q: usize| q * BitArray::BITS_PER_UNIT - self.ones_before[q];
+        let (mut lo, mut hi) = (0, self.bits.arr_size);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if zeros_before(mid) <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(lo * BitArray::BITS_PER_UNIT + select_in_unit(!self.bits.bits[lo], k - zeros_before(lo)))
+    }
+
+    /// Finds the first one at or after the specified index.
+    pub fn next_set_bit(&self, from: usize) -> Option<usize> {
+        self.bits.next_set_bit(from)
+    }
+
+    /// Finds the last one at or before the specified index.
+    pub fn prev_set_bit(&self, at: usize) -> Option<usize> {
+        self.bits.prev_set_bit(at)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn barr_rank_select() {
+        let mut barr = BitArray::new(300);
+        let ones = [0, 5, 127, 128, 129, 200, 255, 256, 299];
+        for &i in &ones {
+            barr.set(i);
+        }
+        let rs = RankSelect::from(barr);
+        for at in 0..=300 {
+            let expected = ones.iter().filter(|&&i| i < at).count();
+            assert_eq!(expected, rs.rank1(at));
+            assert_eq!(at - expected, rs.rank0(at));
+        }
+        for (k, &i) in ones.iter().enumerate() {
+            assert_eq!(Some(i), rs.select1(k));
+        }
+        assert_eq!(None, rs.select1(ones.len()));
+        let zeros = (0..300).filter(|i| !ones.contains(i)).collect::<Vec<_>>();
+        for (k, &i) in zeros.iter().enumerate() {
+            assert_eq!(Some(i), rs.select0(k));
+        }
+        assert_eq!(None, rs.select0(zeros.len()));
+    }
+
+    #[test]
+    fn barr_scan() {
+        let mut barr = BitArray::new(300);
+        let ones = [3, 127, 128, 250, 299];
+        for &i in &ones {
+            barr.set(i);
+        }
+        assert_eq!(ones.to_vec(), barr.iter_ones().collect::<Vec<_>>());
+        for at in 0..300 {
+            assert_eq!(ones.iter().copied().find(|&i| i >= at), barr.next_set_bit(at));
+            assert_eq!(ones.iter().rev().copied().find(|&i| i <= at), barr.prev_set_bit(at));
+            assert_eq!((at..300).find(|i| !ones.contains(i)), barr.next_unset_bit(at));
+            assert_eq!((0..=at).rev().find(|i| !ones.contains(i)), barr.prev_unset_bit(at));
+        }
+        assert_eq!(None, barr.next_set_bit(300));
+        assert_eq!(Some(299), barr.prev_set_bit(300));
+        assert_eq!(Some(298), barr.prev_unset_bit(1000));
+        assert_eq!(None, BitArray::new(0).prev_set_bit(0));
+
+        let full = BitArray::from(&[true; 256]);
+        assert_eq!(None, full.next_unset_bit(0));
+        assert_eq!(Some(255), full.prev_set_bit(255));
+    }
+
     #[test]
     fn barr_set() {
         let mut ba = BitArray::new(4);
@@ -4,9 +4,11 @@ mod multiset;
 mod removable_heap;
 mod median;
 mod bitarray;
+mod ordered_multiset;
 pub use coord_cmp::*;
 pub use max_queue::*;
 pub use multiset::*;
 pub use removable_heap::*;
 pub use median::*;
 pub use bitarray::*;
+pub use ordered_multiset::*;
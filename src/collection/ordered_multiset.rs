@@ -0,0 +1,110 @@
+use crate::order::SparseIndex;
+use crate::range_query::fenwick::FenwickTree;
+
+/// A multiset over a universe of values known in advance, answering order
+/// statistics queries in O(log n). It keeps a frequency table of the
+/// coordinate-compressed values in a FenwickTree.
+pub struct OrderedMultiset {
+    index: SparseIndex,
+    bit: FenwickTree<i64>,
+    len: usize,
+}
+
+impl OrderedMultiset {
+    /// Initializes an empty multiset, given all the values that may be inserted.
+    pub fn new(coords: Vec<i64>) -> Self {
+        let index = SparseIndex::new(coords);
+        let bit = FenwickTree::new(index.len());
+        Self { index, bit, len: 0 }
+    }
+    fn position(&self, val: i64) -> usize {
+        self.index.compress(val)
+            .unwrap_or_else(|_| panic!("{} is not in the universe of the multiset.", val))
+    }
+    /// Inserts one copy of the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was not given on construction.
+    pub fn insert(&mut self, val: i64) {
+        let pos = self.position(val);
+        self.bit.add(pos, 1);
+        self.len += 1;
+    }
+    /// Removes one copy of the value. Returns false if there was none.
+    pub fn erase(&mut self, val: i64) -> bool {
+        if self.count(val) == 0 {
+            return false;
+        }
+        let pos = self.position(val);
+        self.bit.add(pos, -1);
+        self.len -= 1;
+        true
+    }
+    /// Returns the number of copies of the value.
+    pub fn count(&self, val: i64) -> usize {
+        match self.index.compress(val) {
+            Ok(pos) => self.bit.sum(pos, pos + 1) as usize,
+            Err(_) => 0,
+        }
+    }
+    /// Returns the number of elements strictly smaller than the value.
+    pub fn rank(&self, val: i64) -> usize {
+        let pos = self.index.compress(val).unwrap_or_else(|pos| pos);
+        self.bit.sum(0, pos) as usize
+    }
+    /// Returns the k-th smallest element, 0-indexed, counting duplicates.
+    pub fn kth(&self, k: usize) -> Option<i64> {
+        if k >= self.len {
+            return None;
+        }
+        let pos = self.bit.lower_bound(k as i64 + 1);
+        Some(self.index.decompress(pos))
+    }
+    /// Returns the number of elements, counting duplicates.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns true if the multiset contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    pub fn ordered_multiset_test() {
+        let mut ms = OrderedMultiset::new(vec![50, -3, 7, 1_000_000_000, 7]);
+        ms.insert(7);
+        ms.insert(-3);
+        ms.insert(1_000_000_000);
+        ms.insert(7);
+        assert_eq!(4, ms.len());
+        assert_eq!(2, ms.count(7));
+        assert_eq!(0, ms.count(8));
+
+        assert_eq!(Some(-3), ms.kth(0));
+        assert_eq!(Some(7), ms.kth(1));
+        assert_eq!(Some(7), ms.kth(2));
+        assert_eq!(Some(1_000_000_000), ms.kth(3));
+        assert_eq!(None, ms.kth(4));
+
+        assert_eq!(0, ms.rank(-3));
+        assert_eq!(1, ms.rank(0));
+        assert_eq!(1, ms.rank(7));
+        assert_eq!(3, ms.rank(50));
+        assert_eq!(4, ms.rank(i64::MAX));
+
+        assert!(ms.erase(7));
+        assert!(!ms.erase(50));
+        assert_eq!(Some(1_000_000_000), ms.kth(2));
+        assert_eq!(2, ms.rank(51));
+        assert!(ms.erase(7));
+        assert!(ms.erase(-3));
+        assert!(ms.erase(1_000_000_000));
+        assert!(ms.is_empty());
+        assert_eq!(None, ms.kth(0));
+    }
+}
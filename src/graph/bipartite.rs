@@ -0,0 +1,318 @@
+//! Maximum matchings in bipartite graphs, and their Kőnig duals.
+use std::collections::VecDeque;
+
+/// A bipartite graph with left vertices 0..num_left and right vertices
+/// 0..num_right, together with a matching between them.
+pub struct BipartiteMatching {
+    adj: Vec<Vec<usize>>,
+    pub num_left: usize,
+    pub num_right: usize,
+    /// The right vertex matched to each left vertex, if any.
+    pub match_left: Vec<Option<usize>>,
+    /// The left vertex matched to each right vertex, if any.
+    pub match_right: Vec<Option<usize>>,
+}
+
+impl BipartiteMatching {
+    /// Initializes a graph with an empty matching and no edges.
+    pub fn new(num_left: usize, num_right: usize) -> Self {
+        Self {
+            adj: vec![vec![]; num_left],
+            num_left,
+            num_right,
+            match_left: vec![None; num_left],
+            match_right: vec![None; num_right],
+        }
+    }
+
+    /// Adds an edge from left vertex l to right vertex r.
+    pub fn add_edge(&mut self, l: usize, r: usize) {
+        assert!(r < self.num_right);
+        self.adj[l].push(r);
+    }
+
+    /// Lists the matched pairs (l, r) in increasing order of l.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.num_left).filter_map(|l| self.match_left[l].map(|r| (l, r))).collect()
+    }
+
+    fn set_match(&mut self, l: usize, r: usize) {
+        self.match_left[l] = Some(r);
+        self.match_right[r] = Some(l);
+    }
+
+    /// Hopcroft-Karp algorithm: grows the current matching into a maximum
+    /// one in O(E sqrt(V)) time, by repeatedly augmenting along a maximal set
+    /// of disjoint shortest augmenting paths. Returns the matched pairs.
+    pub fn max_matching(&mut self) -> Vec<(usize, usize)> {
+        const INF: usize = usize::MAX;
+        let n = self.num_left;
+        loop {
+            // layers of left vertices by alternating distance from free ones
+            let mut dist = vec![INF; n];
+            let mut queue = (0..n).filter(|&l| self.match_left[l].is_none()).collect::<VecDeque<_>>();
+            for &l in &queue {
+                dist[l] = 0;
+            }
+            let mut found = false;
+            while let Some(l) = queue.pop_front() {
+                for &r in &self.adj[l] {
+                    match self.match_right[r] {
+                        None => found = true,
+                        Some(l2) if dist[l2] == INF => {
+                            dist[l2] = dist[l] + 1;
+                            queue.push_back(l2);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if !found {
+                return self.pairs();
+            }
+
+            // the DFS path is the stack of left vertices, each with the edge
+            // it's currently trying; dead ends are removed from the layers
+            let mut next = vec![0; n];
+            for start in 0..n {
+                if self.match_left[start].is_some() {
+                    continue;
+                }
+                let mut stack = vec![start];
+                while let Some(&l) = stack.last() {
+                    let r = match self.adj[l].get(next[l]) {
+                        Some(&r) => r,
+                        None => {
+                            dist[l] = INF;
+                            stack.pop();
+                            continue;
+                        }
+                    };
+                    match self.match_right[r] {
+                        None => {
+                            for &x in &stack {
+                                let r = self.adj[x][next[x]];
+                                self.set_match(x, r);
+                            }
+                            break;
+                        }
+                        Some(l2) if dist[l2] == dist[l] + 1 => stack.push(l2),
+                        _ => next[l] += 1,
+                    }
+                }
+            }
+        }
+    }
+
+    // Kuhn's search for an augmenting path from the free left vertex start,
+    // through left vertices that aren't blocked. Augments along it if found.
+    fn augment(&mut self, start: usize, blocked: &mut [bool]) -> bool {
+        let mut stack = vec![(start, 0)];
+        blocked[start] = true;
+        while let Some(&mut (l, ref mut i)) = stack.last_mut() {
+            let r = match self.adj[l].get(*i) {
+                Some(&r) => r,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            *i += 1;
+            match self.match_right[r] {
+                None => {
+                    for &(x, i) in &stack {
+                        let r = self.adj[x][i - 1];
+                        self.set_match(x, r);
+                    }
+                    return true;
+                }
+                Some(l2) if !blocked[l2] => {
+                    blocked[l2] = true;
+                    stack.push((l2, 0));
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Finds the maximum matching whose sequence match_left is
+    /// lexicographically smallest, where being unmatched compares greater
+    /// than any match: left vertices are given, in order, the smallest
+    /// partner that keeps the matching maximum. Takes O(E (V + E)) time.
+    pub fn lex_min_matching(&mut self) -> Vec<(usize, usize)> {
+        self.max_matching();
+        let mut fixed = vec![false; self.num_left];
+        for l in 0..self.num_left {
+            fixed[l] = true;
+            let mut cands = self.adj[l].clone();
+            cands.sort_unstable();
+            cands.dedup();
+            for r in cands {
+                if self.match_left[l] == Some(r) {
+                    break;
+                }
+                if self.match_right[r].map_or(false, |l2| fixed[l2]) {
+                    continue;
+                }
+                let (old_left, old_right) = (self.match_left.clone(), self.match_right.clone());
+                // l takes r from its partner l2, if any, releasing its own
+                // partner r0, if any: if both exist, the matching shrinks and
+                // must be augmented without disturbing fixed vertices
+                let shrinks = self.match_left[l].is_some() && self.match_right[r].is_some();
+                if let Some(r0) = self.match_left[l] {
+                    self.match_right[r0] = None;
+                }
+                if let Some(l2) = self.match_right[r] {
+                    self.match_left[l2] = None;
+                }
+                self.set_match(l, r);
+                if !shrinks {
+                    break;
+                }
+                let mut blocked = fixed.clone();
+                let regrown = (l + 1..self.num_left)
+                    .any(|s| self.match_left[s].is_none() && !blocked[s] && self.augment(s, &mut blocked));
+                if regrown {
+                    break;
+                }
+                self.match_left = old_left;
+                self.match_right = old_right;
+            }
+        }
+        self.pairs()
+    }
+
+    // Marks the vertices reachable from free left vertices by alternating
+    // paths, which alternate unmatched edges to the right and matched edges
+    // back to the left.
+    fn alternating_reach(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut left = vec![false; self.num_left];
+        let mut right = vec![false; self.num_right];
+        let mut stack = (0..self.num_left).filter(|&l| self.match_left[l].is_none()).collect::<Vec<_>>();
+        for &l in &stack {
+            left[l] = true;
+        }
+        while let Some(l) = stack.pop() {
+            for &r in &self.adj[l] {
+                if !right[r] {
+                    right[r] = true;
+                    if let Some(l2) = self.match_right[r] {
+                        if !left[l2] {
+                            left[l2] = true;
+                            stack.push(l2);
+                        }
+                    }
+                }
+            }
+        }
+        (left, right)
+    }
+
+    /// Kőnig's theorem: finds a minimum vertex cover, i.e., a smallest set
+    /// of vertices touching every edge, as lists of left and right vertices.
+    /// Its size equals that of a maximum matching.
+    /// NOTE: call max_matching() before use this method.
+    pub fn min_vertex_cover(&self) -> (Vec<usize>, Vec<usize>) {
+        let (left, right) = self.alternating_reach();
+        (
+            (0..self.num_left).filter(|&l| !left[l]).collect(),
+            (0..self.num_right).filter(|&r| right[r]).collect(),
+        )
+    }
+
+    /// Finds a maximum independent set, i.e., a largest set of vertices no
+    /// two of which are adjacent, as lists of left and right vertices. It's
+    /// the complement of a minimum vertex cover.
+    /// NOTE: call max_matching() before use this method.
+    pub fn max_independent_set(&self) -> (Vec<usize>, Vec<usize>) {
+        let (left, right) = self.alternating_reach();
+        (
+            (0..self.num_left).filter(|&l| left[l]).collect(),
+            (0..self.num_right).filter(|&r| !right[r]).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_bipartite_matching() {
+        let mut graph = BipartiteMatching::new(4, 3);
+        for &(l, r) in &[(0, 0), (0, 1), (1, 0), (2, 1), (2, 2), (3, 0)] {
+            graph.add_edge(l, r);
+        }
+        assert_eq!(graph.max_matching().len(), 3);
+        assert_eq!(graph.min_vertex_cover(), (vec![0, 2], vec![0]));
+        assert_eq!(graph.max_independent_set(), (vec![1, 3], vec![1, 2]));
+        // matching 0 to 0 would leave both 1 and 3 unmatched
+        assert_eq!(graph.lex_min_matching(), vec![(0, 1), (1, 0), (2, 2)]);
+    }
+
+    // the lexicographically smallest assignment among maximum matchings
+    fn brute_force(adj: &[Vec<usize>], num_right: usize) -> Vec<usize> {
+        fn search(adj: &[Vec<usize>], used: &mut [bool], cur: &mut Vec<usize>, best: &mut (usize, Vec<usize>)) {
+            let l = cur.len();
+            if l == adj.len() {
+                let size = cur.iter().filter(|&&r| r != usize::MAX).count();
+                if size > best.0 || (size == best.0 && *cur < best.1) {
+                    *best = (size, cur.clone());
+                }
+                return;
+            }
+            let cands = adj[l].iter().copied().filter(|&r| !used[r]).collect::<Vec<_>>();
+            for r in cands {
+                used[r] = true;
+                cur.push(r);
+                search(adj, used, cur, best);
+                cur.pop();
+                used[r] = false;
+            }
+            cur.push(usize::MAX);
+            search(adj, used, cur, best);
+            cur.pop();
+        }
+        let mut best = (0, vec![usize::MAX; adj.len()]);
+        search(adj, &mut vec![false; num_right], &mut vec![], &mut best);
+        best.1
+    }
+
+    #[test]
+    fn test_bipartite_random() {
+        let mut rng = SmallRng::new(23);
+        for _ in 0..200 {
+            let (nl, nr) = (1 + rng.next_u64() as usize % 6, 1 + rng.next_u64() as usize % 6);
+            let mut graph = BipartiteMatching::new(nl, nr);
+            let mut adj = vec![vec![]; nl];
+            for _ in 0..rng.next_u64() % 15 {
+                let (l, r) = (rng.next_u64() as usize % nl, rng.next_u64() as usize % nr);
+                graph.add_edge(l, r);
+                adj[l].push(r);
+            }
+            let expected = brute_force(&adj, nr);
+            let size = expected.iter().filter(|&&r| r != usize::MAX).count();
+
+            let pairs = graph.max_matching();
+            assert_eq!(pairs.len(), size);
+            assert!(pairs.iter().all(|&(l, r)| adj[l].contains(&r)));
+            let (cl, cr) = graph.min_vertex_cover();
+            assert_eq!(cl.len() + cr.len(), size);
+            let (il, ir) = graph.max_independent_set();
+            assert_eq!(il.len() + ir.len(), nl + nr - size);
+            for l in 0..nl {
+                for &r in &adj[l] {
+                    assert!(cl.contains(&l) || cr.contains(&r));
+                    assert!(!il.contains(&l) || !ir.contains(&r));
+                }
+            }
+
+            graph.lex_min_matching();
+            let got = graph.match_left.iter().map(|m| m.unwrap_or(usize::MAX)).collect::<Vec<_>>();
+            assert_eq!(got, expected);
+            assert!((0..nr).all(|r| graph.match_right[r].map_or(true, |l| graph.match_left[l] == Some(r))));
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Maximum matching in general graphs
+use std::collections::VecDeque;
+
+const NONE: usize = usize::MAX;
+
+/// An undirected graph on vertices 0..n together with a matching, which
+/// Edmonds' blossom algorithm grows into a maximum one even when the graph
+/// isn't bipartite: odd cycles (blossoms) met during the search for an
+/// augmenting path are contracted into their base vertex.
+pub struct GeneralMatching {
+    adj: Vec<Vec<usize>>,
+    mate: Vec<usize>,
+    // per-search state: the base of each vertex's blossom, and the parent
+    // of each odd vertex in the alternating tree
+    base: Vec<usize>,
+    parent: Vec<usize>,
+    in_tree: Vec<bool>,
+}
+
+impl GeneralMatching {
+    /// Initializes a graph with an empty matching and no edges.
+    pub fn new(n: usize) -> Self {
+        Self {
+            adj: vec![vec![]; n],
+            mate: vec![NONE; n],
+            base: vec![0; n],
+            parent: vec![NONE; n],
+            in_tree: vec![false; n],
+        }
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Returns whether the graph has no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.adj.is_empty()
+    }
+
+    /// Adds an undirected edge; self-loops are ignored.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        if u != v {
+            self.adj[u].push(v);
+            self.adj[v].push(u);
+        }
+    }
+
+    /// Returns the vertex matched to v, if any.
+    pub fn mate(&self, v: usize) -> Option<usize> {
+        Some(self.mate[v]).filter(|&u| u != NONE)
+    }
+
+    /// Lists the matched pairs (u, v) with u < v, in increasing order of u.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.len()).filter(|&u| u < self.mate[u] && self.mate[u] != NONE).map(|u| (u, self.mate[u])).collect()
+    }
+
+    /// Grows the current matching into a maximum one in O(V^3) time, and
+    /// returns the matched pairs.
+    pub fn max_matching(&mut self) -> Vec<(usize, usize)> {
+        // a greedy matching first saves most of the searches
+        for u in 0..self.len() {
+            if self.mate[u] == NONE {
+                if let Some(&v) = self.adj[u].iter().find(|&&v| self.mate[v] == NONE) {
+                    self.mate[u] = v;
+                    self.mate[v] = u;
+                }
+            }
+        }
+        for root in 0..self.len() {
+            if self.mate[root] == NONE {
+                if let Some(mut v) = self.find_path(root) {
+                    while v != NONE {
+                        let pv = self.parent[v];
+                        let next = self.mate[pv];
+                        self.mate[v] = pv;
+                        self.mate[pv] = v;
+                        v = next;
+                    }
+                }
+            }
+        }
+        self.pairs()
+    }
+
+    // Grows an alternating tree from root by BFS over its even vertices,
+    // and returns the free vertex ending an augmenting path, if any.
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        let n = self.len();
+        for v in 0..n {
+            self.base[v] = v;
+            self.parent[v] = NONE;
+            self.in_tree[v] = false;
+        }
+        self.in_tree[root] = true;
+        let mut queue = VecDeque::from(vec![root]);
+        while let Some(v) = queue.pop_front() {
+            for i in 0..self.adj[v].len() {
+                let to = self.adj[v][i];
+                if self.base[v] == self.base[to] || self.mate[v] == to {
+                    continue;
+                }
+                if to == root || (self.mate[to] != NONE && self.parent[self.mate[to]] != NONE) {
+                    // to is even too, so the edge closes a blossom
+                    let cur_base = self.lca(v, to);
+                    let mut in_blossom = vec![false; n];
+                    self.mark_path(v, cur_base, to, &mut in_blossom);
+                    self.mark_path(to, cur_base, v, &mut in_blossom);
+                    for u in 0..n {
+                        if in_blossom[self.base[u]] {
+                            self.base[u] = cur_base;
+                            if !self.in_tree[u] {
+                                self.in_tree[u] = true;
+                                queue.push_back(u);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NONE {
+                    self.parent[to] = v;
+                    if self.mate[to] == NONE {
+                        return Some(to);
+                    }
+                    self.in_tree[self.mate[to]] = true;
+                    queue.push_back(self.mate[to]);
+                }
+            }
+        }
+        None
+    }
+
+    // Finds the base of the blossom closed by an edge between the even
+    // vertices a and b, as their lowest common ancestor in the tree.
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut seen = vec![false; self.len()];
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            if self.mate[a] == NONE {
+                break;
+            }
+            a = self.parent[self.mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.parent[self.mate[b]];
+        }
+    }
+
+    // Marks the blossoms on the tree path from v up to the base b, and
+    // points the odd vertices on it back across the blossom's closing edge.
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize, in_blossom: &mut [bool]) {
+        while self.base[v] != b {
+            in_blossom[self.base[v]] = true;
+            in_blossom[self.base[self.mate[v]]] = true;
+            self.parent[v] = child;
+            child = self.mate[v];
+            v = self.parent[self.mate[v]];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_blossom() {
+        // a triangle 0-1-2 with a tail 2-3-4, and a pendant 5 on 0: the
+        // greedy matching (0, 1), (2, 3) is augmented through the blossom
+        let mut graph = GeneralMatching::new(6);
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (0, 5)] {
+            graph.add_edge(u, v);
+        }
+        assert_eq!(graph.max_matching(), vec![(0, 5), (1, 2), (3, 4)]);
+        assert_eq!(graph.mate(2), Some(1));
+    }
+
+    #[test]
+    fn test_blossom_random() {
+        let mut rng = SmallRng::new(24);
+        for _ in 0..300 {
+            let n = 1 + rng.next_u64() as usize % 12;
+            let mut graph = GeneralMatching::new(n);
+            let mut adj = vec![0u32; n];
+            for _ in 0..rng.next_u64() % 30 {
+                let (u, v) = (rng.next_u64() as usize % n, rng.next_u64() as usize % n);
+                graph.add_edge(u, v);
+                if u != v {
+                    adj[u] |= 1 << v;
+                    adj[v] |= 1 << u;
+                }
+            }
+            // best[mask] is the maximum matching size among vertices in mask
+            let mut best = vec![0; 1 << n];
+            for mask in 1..1usize << n {
+                let u = mask.trailing_zeros() as usize;
+                let rest = mask & !(1 << u);
+                best[mask] = best[rest];
+                for v in 0..n {
+                    if rest >> v & 1 == 1 && adj[u] >> v & 1 == 1 {
+                        best[mask] = best[mask].max(1 + best[rest & !(1 << v)]);
+                    }
+                }
+            }
+
+            let pairs = graph.max_matching();
+            assert_eq!(pairs.len(), best[(1 << n) - 1]);
+            let mut used = vec![false; n];
+            for &(u, v) in &pairs {
+                assert!(adj[u] >> v & 1 == 1 && !used[u] && !used[v]);
+                used[u] = true;
+                used[v] = true;
+            }
+        }
+    }
+}
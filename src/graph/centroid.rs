@@ -0,0 +1,304 @@
+//! Centroid Decomposition of a tree
+use std::collections::VecDeque;
+
+/// The centroid tree of a tree on vertices 0..n: its root is a centroid of
+/// the whole tree, i.e., a vertex whose removal leaves components of at most
+/// n / 2 vertices, and the children of each centroid are the centroids of
+/// the components left by its removal. The centroid tree has depth
+/// O(log n), and every path of the original tree passes through the
+/// shallowest centroid among its vertices.
+///
+/// Two query patterns are supported:
+///
+/// - for_each_centroid() visits each centroid together with the vertices of
+///   its component, e.g., to count the paths of each length through it;
+/// - ancestors() lists the centroid ancestors of a vertex with their
+///   distances, e.g., to maintain the nearest marked vertex of each centroid.
+///
+/// A forest is decomposed into one centroid tree per tree. Everything is
+/// computed iteratively in O(n log n) time.
+pub struct CentroidDecomposition {
+    /// the parent of each vertex in the centroid tree, or None for a root
+    pub parent: Vec<Option<usize>>,
+    /// the depth of each vertex in the centroid tree, 0 for the root
+    pub level: Vec<usize>,
+    /// dist[v][i] is the distance in the original tree from v to its
+    /// ancestor at level i of the centroid tree, for i in 0..=level[v]
+    pub dist: Vec<Vec<usize>>,
+}
+
+impl CentroidDecomposition {
+    /// Decomposes the tree or forest given by its adjacency lists.
+    pub fn new(adj: &[Vec<usize>]) -> Self {
+        let n = adj.len();
+        let mut parent = vec![None; n];
+        let mut level = vec![0; n];
+        let mut dist = vec![vec![]; n];
+        let mut removed = vec![false; n];
+        let mut size = vec![0; n];
+        let mut up = vec![usize::MAX; n];
+
+        // each tree of the forest starts at its first vertex not yet removed
+        let mut pending = vec![];
+        for root in (0..n).rev() {
+            pending.push((root, None));
+        }
+        while let Some((start, par)) = pending.pop() {
+            if par.is_none() && removed[start] {
+                continue;
+            }
+            // lists the component in BFS order, to compute subtree sizes
+            let mut order = vec![start];
+            up[start] = usize::MAX;
+            let mut i = 0;
+            while i < order.len() {
+                let u = order[i];
+                for &v in &adj[u] {
+                    if v != up[u] && !removed[v] {
+                        up[v] = u;
+                        order.push(v);
+                    }
+                }
+                i += 1;
+            }
+            for &u in order.iter().rev() {
+                size[u] = 1 + adj[u].iter().filter(|&&v| v != up[u] && !removed[v]).map(|&v| size[v]).sum::<usize>();
+            }
+            let total = order.len();
+            let mut c = start;
+            while let Some(&v) = adj[c].iter().find(|&&v| v != up[c] && !removed[v] && 2 * size[v] > total) {
+                c = v;
+            }
+
+            removed[c] = true;
+            parent[c] = par;
+            level[c] = par.map_or(0, |p| level[p] + 1);
+            for (v, d) in Self::bfs(adj, &removed, c).into_iter().flatten() {
+                dist[v].push(d);
+            }
+            dist[c].push(0);
+            for &v in &adj[c] {
+                if !removed[v] {
+                    pending.push((v, Some(c)));
+                }
+            }
+        }
+        Self { parent, level, dist }
+    }
+
+    // Lists the vertices reachable from c without going through removed
+    // vertices, with their distances, grouped by c's neighbors.
+    fn bfs(adj: &[Vec<usize>], removed: &[bool], c: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut groups = vec![];
+        for &s in &adj[c] {
+            if removed[s] {
+                continue;
+            }
+            let mut group = vec![(s, 1)];
+            let mut queue = VecDeque::from(vec![(s, c, 1)]);
+            while let Some((u, p, d)) = queue.pop_front() {
+                for &v in &adj[u] {
+                    if v != p && !removed[v] {
+                        group.push((v, d + 1));
+                        queue.push_back((v, u, d + 1));
+                    }
+                }
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
+    /// Returns the root of the centroid tree, if the tree isn't empty. On a
+    /// forest, returns the root with the smallest index; the root over any
+    /// vertex v is ancestors(v)[0].
+    pub fn root(&self) -> Option<usize> {
+        self.level.iter().position(|&l| l == 0)
+    }
+
+    /// Lists the ancestors of v in the centroid tree, from the root down to
+    /// v itself, together with their distances from v in the original tree.
+    pub fn ancestors(&self, v: usize) -> Vec<(usize, usize)> {
+        let mut res = Vec::with_capacity(self.level[v] + 1);
+        let mut a = Some(v);
+        while let Some(u) = a {
+            res.push((u, self.dist[v][self.level[u]]));
+            a = self.parent[u];
+        }
+        res.reverse();
+        res
+    }
+
+    /// Calls visit(c, groups) for each centroid c, where groups lists the
+    /// other vertices of c's component, grouped by the neighbor of c through
+    /// which they're reached, together with their distances from c.
+    /// Every path of the tree through c, other than c alone, is made of c
+    /// and vertices of at most two groups. This takes O(n log n) time.
+    pub fn for_each_centroid(&self, adj: &[Vec<usize>], mut visit: impl FnMut(usize, &[Vec<(usize, usize)>])) {
+        let n = adj.len();
+        let mut by_level = (0..n).collect::<Vec<_>>();
+        by_level.sort_by_key(|&v| self.level[v]);
+        // Vertices at lower levels are removed, as well as c itself.
+        let mut removed = vec![false; n];
+        for c in by_level {
+            removed[c] = true;
+            let groups = Self::bfs(adj, &removed, c);
+            visit(c, &groups);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    fn random_tree(rng: &mut SmallRng, n: usize) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; n];
+        for v in 1..n {
+            let p = rng.next_u64() as usize % v;
+            adj[p].push(v);
+            adj[v].push(p);
+        }
+        adj
+    }
+
+    fn all_dists(adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        (0..adj.len())
+            .map(|s| {
+                let mut d = vec![usize::MAX; adj.len()];
+                d[s] = 0;
+                let mut queue = VecDeque::from(vec![s]);
+                while let Some(u) = queue.pop_front() {
+                    for &v in &adj[u] {
+                        if d[v] == usize::MAX {
+                            d[v] = d[u] + 1;
+                            queue.push_back(v);
+                        }
+                    }
+                }
+                d
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_centroid_tree() {
+        // a path 0 - 1 - 2 - 3 - 4 - 5 - 6
+        let adj = (0..7)
+            .map(|v: usize| [v.wrapping_sub(1), v + 1].iter().copied().filter(|&u| u < 7).collect())
+            .collect::<Vec<Vec<_>>>();
+        let cd = CentroidDecomposition::new(&adj);
+
+        assert_eq!(cd.root(), Some(3));
+        assert_eq!(cd.parent[1], Some(3));
+        assert_eq!(cd.parent[0], Some(1));
+        assert_eq!(cd.level, vec![2, 1, 2, 0, 2, 1, 2]);
+        assert_eq!(cd.ancestors(6), vec![(3, 3), (5, 1), (6, 0)]);
+
+        // two disjoint edges
+        let cd = CentroidDecomposition::new(&[vec![1], vec![0], vec![3], vec![2]]);
+        assert_eq!(cd.parent, vec![None, Some(0), None, Some(2)]);
+        assert_eq!(cd.ancestors(3), vec![(2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn test_forest() {
+        let mut rng = SmallRng::new(20);
+        for &n in &[1, 5, 50, 200] {
+            // a random tree with about a quarter of its edges removed
+            let mut adj = vec![vec![]; n];
+            for v in 1..n {
+                if rng.next_u64() % 4 != 0 {
+                    let p = rng.next_u64() as usize % v;
+                    adj[p].push(v);
+                    adj[v].push(p);
+                }
+            }
+            let dists = all_dists(&adj);
+            let cd = CentroidDecomposition::new(&adj);
+            for v in 0..n {
+                let ancestors = cd.ancestors(v);
+                assert_eq!(cd.level[ancestors[0].0], 0);
+                assert!(1 << cd.level[v] <= n);
+                for (a, d) in ancestors {
+                    assert_eq!(dists[v][a], d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_paths_by_length() {
+        let mut rng = SmallRng::new(18);
+        for &n in &[1, 2, 5, 50, 200] {
+            let adj = random_tree(&mut rng, n);
+            let dists = all_dists(&adj);
+            let mut expected = vec![0u64; n];
+            for u in 0..n {
+                for v in u + 1..n {
+                    expected[dists[u][v]] += 1;
+                }
+            }
+
+            let cd = CentroidDecomposition::new(&adj);
+            assert!(cd.level.iter().all(|&l| 1 << l <= n));
+            let mut counts = vec![0u64; n];
+            cd.for_each_centroid(&adj, |_, groups| {
+                // paths with the centroid as an endpoint, then across groups
+                let mut seen = vec![1u64];
+                for group in groups {
+                    let mut here = vec![];
+                    for &(_, d) in group {
+                        if here.len() <= d {
+                            here.resize(d + 1, 0);
+                        }
+                        here[d] += 1;
+                    }
+                    for (d1, &c1) in here.iter().enumerate() {
+                        for (d2, &c2) in seen.iter().enumerate() {
+                            counts[d1 + d2] += c1 * c2;
+                        }
+                    }
+                    if seen.len() < here.len() {
+                        seen.resize(here.len(), 0);
+                    }
+                    for (d, &c) in here.iter().enumerate() {
+                        seen[d] += c;
+                    }
+                }
+            });
+            assert_eq!(counts, expected);
+        }
+    }
+
+    #[test]
+    fn test_nearest_marked_vertex() {
+        let mut rng = SmallRng::new(19);
+        let n = 100;
+        let adj = random_tree(&mut rng, n);
+        let dists = all_dists(&adj);
+        let cd = CentroidDecomposition::new(&adj);
+        let mut nearest = vec![usize::MAX; n];
+        let mut marked = vec![];
+
+        for _ in 0..200 {
+            let v = rng.next_u64() as usize % n;
+            if rng.next_u64() % 3 == 0 {
+                marked.push(v);
+                for (a, d) in cd.ancestors(v) {
+                    nearest[a] = nearest[a].min(d);
+                }
+            } else {
+                let ans = cd
+                    .ancestors(v)
+                    .into_iter()
+                    .map(|(a, d)| nearest[a].saturating_add(d))
+                    .min()
+                    .unwrap();
+                let expected = marked.iter().map(|&u| dists[u][v]).min().unwrap_or(usize::MAX);
+                assert_eq!(ans, expected);
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 //! Graph connectivity structures.
+use super::{Edge, Graph};
 
 /// Helper struct that carries data needed for the depth-first searches in
-/// ConnectivityGraph's constructor.
+/// ConnectivityGraph's constructor. The searches use an explicit stack, so
+/// they don't overflow the call stack on deep graphs.
 struct ConnectivityData {
     time: usize,
     vis: Box<[usize]>,
@@ -95,6 +97,7 @@ impl ConnectivityGraph {
     /// odd-numbered successor as its negation, then we can build the
     /// implication graph corresponding to any 2-CNF formula.
     /// Note that u||v == !u -> v == !v -> u.
+    /// See two_sat::TwoSat for a friendlier interface.
     pub fn add_two_sat_clause(&mut self, u: usize, v: usize) {
         self.add_edge(u ^ 1, v);
         self.add_edge(v ^ 1, u);
@@ -131,22 +134,31 @@ impl ConnectivityGraph {
     // I think this logic comes from here.
     //  https://www.youtube.com/watch?v=wUgWX0nc4NY
     //  https://github.com/williamfiset/Algorithms/blob/86661d3daf3063eae2ea9329b069456b87490b62/src/main/java/com/williamfiset/algorithms/graphtheory/TarjanSccSolverAdjacencyList.java#L78
-    fn scc(&self, data: &mut ConnectivityData, u: usize) {
-        data.visit(u);
-        for &(_,v) in &self.adj[u] {
-            if data.vis[v] == 0 {
-                self.scc(data, v);
-            }
-            if data.cc[v] == 0 {
-                data.lower(u, data.low[v]);
-            }
-        }
-        if data.vis[u] == data.low[u] {
-            data.num_cc += 1;
-            while let Some(v) = data.v_stack.pop() {
-                data.cc[v] = data.num_cc;
-                if v == u {
-                    break;
+    // Each stack frame (u, i) resumes u's loop at its i-th edge; a frame is
+    // pushed back unchanged before descending, so the edge to a child is
+    // revisited once the child is done.
+    fn scc(&self, data: &mut ConnectivityData, root: usize) {
+        data.visit(root);
+        let mut stack = vec![(root, 0)];
+        while let Some((u, i)) = stack.pop() {
+            if let Some(&(_,v)) = self.adj[u].get(i) {
+                if data.vis[v] == 0 {
+                    stack.push((u, i));
+                    data.visit(v);
+                    stack.push((v, 0));
+                    continue;
+                }
+                if data.cc[v] == 0 {
+                    data.lower(u, data.low[v]);
+                }
+                stack.push((u, i + 1));
+            } else if data.vis[u] == data.low[u] {
+                data.num_cc += 1;
+                while let Some(v) = data.v_stack.pop() {
+                    data.cc[v] = data.num_cc;
+                    if v == u {
+                        break;
+                    }
                 }
             }
         }
@@ -178,39 +190,48 @@ impl ConnectivityGraph {
         vertices
     }
 
-    fn bcc(&self, data: &mut ConnectivityData, u: usize, par: usize) {
-        data.visit(u);
-        for &(e,v) in &self.adj[u] {
-            if data.vis[v] == 0 {
-                data.e_stack.push(e);
-                self.bcc(data, v, e);
-                data.lower(u, data.low[v]);
-                if data.vis[u] <= data.low[v] { // no back-edge in subtree
-                    // u is a cut vertex unless it's a one-child root
-                    data.num_vcc += 1;
-                    while let Some(top_e) = data.e_stack.pop() {
-                        data.vcc[top_e] = data.num_vcc;
-                        if e == top_e {
-                            break;
+    // Each stack frame (u, par, i, is_back) resumes u's loop at its i-th
+    // edge, where is_back tells whether we're back from the child it leads to.
+    fn bcc(&self, data: &mut ConnectivityData, root: usize, root_par: usize) {
+        data.visit(root);
+        let mut stack = vec![(root, root_par, 0, false)];
+        while let Some((u, par, i, is_back)) = stack.pop() {
+            if let Some(&(e,v)) = self.adj[u].get(i) {
+                if is_back {
+                    data.lower(u, data.low[v]);
+                    if data.vis[u] <= data.low[v] { // no back-edge in subtree
+                        // u is a cut vertex unless it's a one-child root
+                        data.num_vcc += 1;
+                        while let Some(top_e) = data.e_stack.pop() {
+                            data.vcc[top_e] = data.num_vcc;
+                            if e == top_e {
+                                break;
+                            }
                         }
                     }
+                } else if data.vis[v] == 0 {
+                    data.e_stack.push(e);
+                    stack.push((u, par, i, true));
+                    data.visit(v);
+                    stack.push((v, e, 0, false));
+                    continue;
+                } else if data.vis[v] < data.vis[u] && e != par { // found a back-edge and u is not parent of v.
+                    data.lower(u, data.vis[v]);
+                    data.e_stack.push(e);
+                } else if v == u {
+                    // e is a self-loop
+                    data.num_vcc += 1;
+                    data.vcc[e] = data.num_vcc;
                 }
-            } else if data.vis[v] < data.vis[u] && e != par { // found a back-edge and u is not parent of v.
-                data.lower(u, data.vis[v]);
-                data.e_stack.push(e);
-            } else if v == u {
-                // e is a self-loop
-                data.num_vcc += 1;
-                data.vcc[e] = data.num_vcc;
-            }
-        }
-        if data.vis[u] == data.low[u] {
-            // par is a cut edge unless par==-1
-            data.num_cc += 1;
-            while let Some(v) = data.v_stack.pop() {
-                data.cc[v] = data.num_cc;
-                if v == u {
-                    break;
+                stack.push((u, par, i + 1, false));
+            } else if data.vis[u] == data.low[u] {
+                // par is a cut edge unless par==-1
+                data.num_cc += 1;
+                while let Some(v) = data.v_stack.pop() {
+                    data.cc[v] = data.num_cc;
+                    if v == u {
+                        break;
+                    }
                 }
             }
         }
@@ -234,6 +255,55 @@ impl ConnectivityGraph {
         }
         return false;
     }
+
+    /// In an undirected graph, builds the bridge tree: the forest whose
+    /// vertices are the 2ECCs, joined by one edge per bridge, in increasing
+    /// order of bridge ids. Also returns the tree vertex of each vertex,
+    /// which is its 2ECC cc[u] - 1.
+    /// NOTE: call build(false) before use this method.
+    pub fn bridge_tree(&self) -> (Graph<Edge>, Vec<usize>) {
+        let comp = self.cc.iter().map(|&c| c - 1).collect::<Vec<_>>();
+        let mut tree = Graph::new(self.num_cc, self.num_cc.saturating_sub(1));
+        for (e, &(u, v)) in self.edges.iter().enumerate() {
+            if self.is_cut_edge(e) {
+                tree.add_undirected_edge(comp[u], comp[v]);
+            }
+        }
+        (tree, comp)
+    }
+
+    /// In an undirected graph, builds the block-cut tree: the forest whose
+    /// vertices are first the 2VCCs (blocks), with block vcc[e] - 1 holding
+    /// edge e, then the cut vertices and the isolated vertices, in increasing
+    /// order. Each cut vertex is joined to the blocks containing it.
+    /// Also returns the tree vertex of each vertex: its own if it has one,
+    /// or else the unique block containing it.
+    /// NOTE: call build(false) before use this method.
+    pub fn block_cut_tree(&self) -> (Graph<Edge>, Vec<usize>) {
+        let mut num_nodes = self.num_vcc;
+        let node = (0..self.num_v)
+            .map(|u| match self.adj[u].first() {
+                Some(&(e, _)) if !self.is_cut_vertex(u) => self.vcc[e] - 1,
+                _ => {
+                    num_nodes += 1;
+                    num_nodes - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut tree = Graph::new(num_nodes, num_nodes);
+        for (u, &x) in node.iter().enumerate() {
+            if self.is_cut_vertex(u) {
+                let mut blocks = self.adj[u].iter().map(|&(e, _)| self.vcc[e] - 1).collect::<Vec<_>>();
+                blocks.sort_unstable();
+                blocks.dedup();
+                for b in blocks {
+                    tree.add_undirected_edge(x, b);
+                }
+            }
+        }
+        (tree, node)
+    }
 }
 
 #[cfg(test)]
@@ -311,4 +381,63 @@ mod test {
         assert_eq!(bridges, vec![0]);
         assert_eq!(articulation_points, vec![1]);
     }
+
+    #[test]
+    fn test_long_path() {
+        let n = 1_000_000;
+        let mut graph = ConnectivityGraph::new(n);
+        for u in 1..n {
+            graph.add_undirected_edge(u - 1, u);
+        }
+        graph.build(false);
+        assert_eq!(graph.num_cc, n);
+        assert_eq!(graph.num_vcc, n - 1);
+        assert!(graph.is_cut_vertex(n / 2));
+        assert!(!graph.is_cut_vertex(n - 1));
+        assert!(graph.is_cut_edge(12345));
+        graph.build(true);
+        assert_eq!(graph.num_cc, 1);
+
+        let mut graph = ConnectivityGraph::new(n);
+        for u in 1..n {
+            graph.add_edge(u - 1, u);
+        }
+        graph.add_edge(n - 1, n / 2);
+        graph.build(true);
+        assert_eq!(graph.num_cc, n / 2 + 1);
+        assert_eq!(graph.topological_sort()[..3], [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_bridge_and_block_cut_trees() {
+        // triangles 0-1-2 and 2-3-4, a bridge 4-5, and an isolated vertex 6
+        let mut graph = ConnectivityGraph::new(7);
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2), (4, 5)] {
+            graph.add_undirected_edge(u, v);
+        }
+        graph.build(false);
+
+        let (tree, comp) = graph.bridge_tree();
+        assert_eq!(tree.num_v(), 3);
+        assert_eq!(tree.num_e(), 1);
+        assert!((0..5).all(|u| comp[u] == comp[0]));
+        assert_ne!(comp[5], comp[0]);
+        assert_ne!(comp[6], comp[0]);
+        assert_eq!(*tree.edge(0), Edge { u: comp[4], v: comp[5] });
+
+        let (tree, node) = graph.block_cut_tree();
+        assert_eq!(graph.num_vcc, 3);
+        assert_eq!(tree.num_v(), 6);
+        assert_eq!(tree.num_e(), 4);
+        let block = |e: usize| graph.vcc[e] - 1;
+        assert_eq!(&node[..2], &[block(0), block(0)]);
+        assert_eq!(node[3], block(3));
+        assert_eq!(node[5], block(6));
+        assert_eq!(&node[2..], &[3, block(3), 4, block(6), 5]);
+        let mut tree_edges = (0..4).map(|e| (tree.edge(e).u, tree.edge(e).v)).collect::<Vec<_>>();
+        tree_edges.sort_unstable();
+        let mut expected = vec![(3, block(0)), (3, block(3)), (4, block(3)), (4, block(6))];
+        expected.sort_unstable();
+        assert_eq!(tree_edges, expected);
+    }
 }
@@ -14,6 +14,10 @@ pub struct Dinic {
     num_vert: usize,
     edges: Vec<FlowEdge>,
     distance: Vec<i64>,
+    // lower bound of each edge, negated on residual edges
+    lower: Vec<i64>,
+    // required net inflow of each vertex, after subtracting lower bounds
+    need: Vec<i64>,
 }
 
 impl Dinic {
@@ -27,6 +31,8 @@ impl Dinic {
             num_vert: vmax,
             edges: Vec::with_capacity(2*emax_hint),
             distance: vec![],
+            lower: Vec::with_capacity(2*emax_hint),
+            need: vec![0; vmax],
         }
     }
 
@@ -48,9 +54,22 @@ impl Dinic {
         // add a residual edge
         self.adj[v].push((edge_id+1,u));
         self.edges.push(FlowEdge { v:u, u:v, cap:rcap, flow:0 });
+        self.lower.extend_from_slice(&[0, 0]);
         return (edge_id,edge_id+1);
     }
 
+    // Removes the vertices and edges added after the first num_v and num_e,
+    // which come last in every adjacency list.
+    fn truncate(&mut self, num_v: usize, num_e: usize) {
+        while self.num_e() > num_e {
+            let e = self.edges.pop().unwrap();
+            self.lower.pop();
+            self.adj[e.u].pop();
+        }
+        self.adj.truncate(num_v);
+        self.num_vert = num_v;
+    }
+
     /// Adds an edge with rcap == 0.
     pub fn add_edge(&mut self, u: usize, v: usize, cap: i64) -> (usize,usize) {
        return self.add_edge_rcap(u,v,cap,0);
@@ -63,6 +82,95 @@ impl Dinic {
         return self.add_flow_edge(u,v,cap,rcap);
     }
 
+    /// Adds an edge whose flow must lie between lower and upper, with
+    /// rcap == 0. Lower bounds are enforced by feasible_circulation(),
+    /// max_flow_with_bounds() and min_flow_with_bounds(), but ignored by
+    /// dinic(); the flow field of the edges doesn't include them.
+    /// Returns the IDs of the added edge and residual edge.
+    pub fn add_edge_bounds(&mut self, u: usize, v: usize, lower: i64, upper: i64) -> (usize,usize) {
+        assert!(0 <= lower && lower <= upper, "invalid bounds [{}, {}]", lower, upper);
+        let (e, re) = self.add_flow_edge(u,v,upper-lower,0);
+        self.lower[e] = lower;
+        self.lower[re] = -lower;
+        self.need[u] += lower;
+        self.need[v] -= lower;
+        (e, re)
+    }
+
+    /// Requires the net inflow into v to be d, i.e., v consumes d units of
+    /// flow, or produces -d units if d is negative. Like lower bounds,
+    /// demands are only enforced by the *_with_bounds() methods and
+    /// feasible_circulation().
+    pub fn add_demand(&mut self, v: usize, d: i64) {
+        self.need[v] += d;
+    }
+
+    /// Returns the flow on an edge, including its lower bound.
+    pub fn flow(&self, e: usize) -> i64 {
+        self.edges[e].flow + self.lower[e]
+    }
+
+    // Starting from zero flow, routes the demands from a super source to a
+    // super sink, added temporarily, and returns whether they're all met.
+    fn satisfy_bounds(&mut self) -> bool {
+        let (n, m) = (self.num_v(), self.num_e());
+        if self.need.iter().sum::<i64>() != 0 {
+            return false;
+        }
+        self.adj.resize(n + 2, vec![]);
+        self.num_vert = n + 2;
+        let mut total = 0;
+        for v in 0..n {
+            let d = self.need[v];
+            if d > 0 {
+                self.add_flow_edge(v, n + 1, d, 0);
+                total += d;
+            } else if d < 0 {
+                self.add_flow_edge(n, v, -d, 0);
+            }
+        }
+        let flow = self.dinic(n, n + 1);
+        self.truncate(n, m);
+        flow == total
+    }
+
+    /// Finds a flow satisfying all lower bounds, capacities and demands,
+    /// where every other vertex conserves flow, or returns false if there's
+    /// none. Previous flow values are cleared.
+    pub fn feasible_circulation(&mut self) -> bool {
+        self.clear_flow();
+        self.satisfy_bounds()
+    }
+
+    // Finds a feasible flow with s and t exempt from conservation, through
+    // a temporary edge between t and s, and returns its s-t value, which
+    // may be negative.
+    fn feasible_st_flow(&mut self, s: usize, t: usize) -> Option<i64> {
+        self.clear_flow();
+        let (n, m) = (self.num_v(), self.num_e());
+        self.add_flow_edge(t,s,Self::INF / 2,Self::INF / 2);
+        let feasible = self.satisfy_bounds();
+        let value = self.edges[m].flow;
+        self.truncate(n, m);
+        if feasible { Some(value) } else { None }
+    }
+
+    /// Finds a maximum flow from s to t satisfying all lower bounds,
+    /// capacities and demands, where vertices other than s and t conserve
+    /// flow. Returns its value, or None if there's no feasible flow; the
+    /// value is negative if the bounds force flow from t to s.
+    /// Per-edge flows are available through flow().
+    pub fn max_flow_with_bounds(&mut self, s: usize, t: usize) -> Option<i64> {
+        let value = self.feasible_st_flow(s, t)?;
+        Some(value + self.dinic(s, t))
+    }
+
+    /// Like max_flow_with_bounds(), but finds a minimum flow from s to t.
+    pub fn min_flow_with_bounds(&mut self, s: usize, t: usize) -> Option<i64> {
+        let value = self.feasible_st_flow(s, t)?;
+        Some(value - self.dinic(t, s))
+    }
+
     /// Gets an edge by the edge id.
     pub fn get_edge(&self, i: usize) -> &FlowEdge{
         return &self.edges[i];
@@ -177,6 +285,13 @@ impl Dinic {
             .collect()
     }
 
+    /// Returns whether each vertex is reachable from s in the residual
+    /// graph, i.e., lies on the source side of a minimum cut.
+    /// After running maximum flow, use this to recover the cut's vertices.
+    pub fn source_side(&self) -> Vec<bool> {
+        (0..self.num_v()).map(|v| self.distance[v] < Self::INF).collect()
+    }
+
     pub fn debug_print(&self, residual: bool) {
         if cfg!(debug_assertions) {
             let step = if residual { 1 } else { 2 };
@@ -190,6 +305,7 @@ impl Dinic {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::graph::flow::brute_force_flows;
 
     #[test]
     fn test_dinic() {
@@ -214,6 +330,83 @@ mod test {
         println!("max: {:?}", max);
         assert_eq!(max, 3);
         assert_eq!(&[false,false,true,false], &*graph.min_cut());
+        assert_eq!(vec![true,true,false], graph.source_side());
+    }
+
+    #[test]
+    fn test_dinic_bounds() {
+        let mut graph = Dinic::new(4, 5);
+        let e01 = graph.add_edge_bounds(0, 1, 2, 5).0;
+        let e02 = graph.add_edge_bounds(0, 2, 0, 3).0;
+        let e12 = graph.add_edge_bounds(1, 2, 0, 1).0;
+        let e13 = graph.add_edge_bounds(1, 3, 3, 4).0;
+        let e23 = graph.add_edge_bounds(2, 3, 1, 2).0;
+        assert_eq!(graph.max_flow_with_bounds(0, 3), Some(6));
+        assert_eq!(graph.flow(e13) + graph.flow(e23), 6);
+        assert_eq!(graph.min_flow_with_bounds(0, 3), Some(4));
+        assert_eq!(graph.flow(e01), graph.flow(e12) + graph.flow(e13));
+        assert!(graph.flow(e02) >= 0 && graph.flow(e13) >= 3);
+        // no circulation: edges out of 0 need flow, but nothing comes back
+        assert!(!graph.feasible_circulation());
+
+        let e30 = graph.add_edge_bounds(3, 0, 0, 10).0;
+        assert!(graph.feasible_circulation());
+        assert!((4..=6).contains(&graph.flow(e30)));
+        graph.add_demand(3, 7);
+        graph.add_demand(0, -7);
+        assert!(!graph.feasible_circulation());
+        assert_eq!(graph.num_v(), 4);
+        assert_eq!(graph.num_e(), 12);
+    }
+
+    #[test]
+    fn test_dinic_bounds_random() {
+        let mut rng = crate::rng::SmallRng::new(26);
+        for _ in 0..300 {
+            let n = 2 + rng.next_u64() as usize % 4;
+            let edges = (0..rng.next_u64() % 7)
+                .map(|_| {
+                    let lower = (rng.next_u64() % 3) as i64;
+                    let u = rng.next_u64() as usize % n;
+                    (u, (u + 1 + rng.next_u64() as usize % (n - 1)) % n, lower, lower + (rng.next_u64() % 3) as i64)
+                })
+                .collect::<Vec<_>>();
+            let mut graph = Dinic::new(n, edges.len());
+            for &(u, v, l, r) in &edges {
+                graph.add_edge_bounds(u, v, l, r);
+            }
+            // the values of flows conserved everywhere but at 0 and n - 1
+            let all_flows = brute_force_flows(n, &edges.iter().map(|&(u, v, l, r)| (u, v, l, r, 0)).collect::<Vec<_>>());
+            let values = all_flows
+                .iter()
+                .filter(|(net, _)| (1..n - 1).all(|v| net[v] == 0))
+                .map(|(net, _)| net[n - 1])
+                .collect::<Vec<_>>();
+            let check = |graph: &Dinic, value: i64| {
+                let mut net = vec![0; n];
+                for (i, &(u, v, l, r)) in edges.iter().enumerate() {
+                    let f = graph.flow(2 * i);
+                    assert!(l <= f && f <= r);
+                    net[u] -= f;
+                    net[v] += f;
+                }
+                assert!((1..n - 1).all(|v| net[v] == 0));
+                assert_eq!(net[n - 1], value);
+            };
+
+            let max = graph.max_flow_with_bounds(0, n - 1);
+            assert_eq!(max, values.iter().copied().max());
+            if let Some(max) = max {
+                check(&graph, max);
+            }
+            let min = graph.min_flow_with_bounds(0, n - 1);
+            assert_eq!(min, values.iter().copied().min());
+            if let Some(min) = min {
+                check(&graph, min);
+            }
+            let circulation = all_flows.iter().any(|(net, _)| net.iter().all(|&x| x == 0));
+            assert_eq!(graph.feasible_circulation(), circulation);
+        }
     }
 
     #[test]
@@ -0,0 +1,167 @@
+//! Dominator Tree of a directed graph
+use super::{Edge, Graph};
+
+const NONE: usize = usize::MAX;
+
+impl Graph<Edge> {
+    /// Lengauer-Tarjan algorithm for the dominator tree of a directed graph
+    /// from root: a vertex d dominates v if every path from root to v passes
+    /// through d. Returns the immediate dominator of each vertex, i.e., its
+    /// parent in the dominator tree, with root mapped to itself and vertices
+    /// unreachable from root mapped to None.
+    /// Runs iteratively in O(E log V) time.
+    pub fn dominators(&self, root: usize) -> Vec<Option<usize>> {
+        let n = self.num_v();
+        let mut succ = vec![vec![]; n];
+        let mut pred = vec![vec![]; n];
+        for e in &self.edges {
+            succ[e.u].push(e.v);
+            pred[e.v].push(e.u);
+        }
+
+        // Vertices are renamed by their preorder in a DFS tree from root.
+        let mut num = vec![NONE; n];
+        let mut order = Vec::with_capacity(n);
+        let mut par = Vec::with_capacity(n);
+        let mut stack = vec![(root, NONE)];
+        while let Some((u, p)) = stack.pop() {
+            if num[u] != NONE {
+                continue;
+            }
+            num[u] = order.len();
+            order.push(u);
+            par.push(p);
+            for &v in succ[u].iter().rev() {
+                if num[v] == NONE {
+                    stack.push((v, num[u]));
+                }
+            }
+        }
+
+        let cnt = order.len();
+        let mut semi = (0..cnt).collect::<Vec<_>>();
+        let mut idom = vec![0; cnt];
+        let mut anc = vec![NONE; cnt];
+        let mut label = (0..cnt).collect::<Vec<_>>();
+        let mut bucket = vec![vec![]; cnt];
+        let mut path = vec![];
+
+        // Returns the vertex with the smallest semidominator on the path from
+        // v up to, but excluding, the root of its tree in the forest built
+        // so far, compressing the path along the way.
+        let mut eval = |v: usize, anc: &mut Vec<usize>, label: &mut Vec<usize>, semi: &[usize]| {
+            if anc[v] == NONE {
+                return v;
+            }
+            let mut x = v;
+            while anc[anc[x]] != NONE {
+                path.push(x);
+                x = anc[x];
+            }
+            while let Some(x) = path.pop() {
+                if semi[label[anc[x]]] < semi[label[x]] {
+                    label[x] = label[anc[x]];
+                }
+                anc[x] = anc[anc[x]];
+            }
+            label[v]
+        };
+
+        for w in (1..cnt).rev() {
+            for &v in &pred[order[w]] {
+                if num[v] != NONE {
+                    let u = eval(num[v], &mut anc, &mut label, &semi);
+                    semi[w] = semi[w].min(semi[u]);
+                }
+            }
+            bucket[semi[w]].push(w);
+            anc[w] = par[w];
+            for v in std::mem::take(&mut bucket[par[w]]) {
+                let u = eval(v, &mut anc, &mut label, &semi);
+                idom[v] = if semi[u] < semi[v] { u } else { par[w] };
+            }
+        }
+        for w in 1..cnt {
+            if idom[w] != semi[w] {
+                idom[w] = idom[idom[w]];
+            }
+        }
+
+        let mut res = vec![None; n];
+        for (w, &v) in order.iter().enumerate() {
+            res[v] = Some(order[idom[w]]);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    fn reachable(graph: &Graph<Edge>, root: usize, removed: usize) -> Vec<bool> {
+        let mut seen = vec![false; graph.num_v()];
+        if root == removed {
+            return seen;
+        }
+        seen[root] = true;
+        let mut stack = vec![root];
+        while let Some(u) = stack.pop() {
+            for a in graph.adj_list(u) {
+                if a.v != removed && !seen[a.v] {
+                    seen[a.v] = true;
+                    stack.push(a.v);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn test_dominators() {
+        let mut graph = Graph::new(7, 8);
+        for &(u, v) in &[(0, 1), (1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (5, 1), (6, 4)] {
+            graph.add_edge(u, v);
+        }
+        let idom = graph.dominators(0);
+        assert_eq!(idom, vec![Some(0), Some(0), Some(1), Some(1), Some(1), Some(4), None]);
+    }
+
+    #[test]
+    fn test_dominators_random() {
+        let mut rng = SmallRng::new(20);
+        for &(n, m) in &[(1, 0), (5, 8), (10, 15), (30, 60), (30, 200)] {
+            for _ in 0..10 {
+                let mut graph = Graph::new(n, m);
+                for _ in 0..m {
+                    let u = rng.next_u64() as usize % n;
+                    let v = rng.next_u64() as usize % n;
+                    graph.add_edge(u, v);
+                }
+                let root = rng.next_u64() as usize % n;
+                let idom = graph.dominators(root);
+
+                let reach = reachable(&graph, root, usize::MAX);
+                // dom[d][v] iff d dominates v
+                let dom = (0..n)
+                    .map(|d| {
+                        let without = reachable(&graph, root, d);
+                        (0..n).map(|v| reach[v] && (v == d || !without[v])).collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                for v in 0..n {
+                    let expected = if !reach[v] {
+                        None
+                    } else if v == root {
+                        Some(root)
+                    } else {
+                        // the strict dominator dominated by all the others
+                        (0..n).filter(|&d| d != v && dom[d][v]).max_by_key(|&d| (0..n).filter(|&e| dom[e][d]).count())
+                    };
+                    assert_eq!(idom[v], expected);
+                }
+            }
+        }
+    }
+}
@@ -15,6 +15,10 @@ pub struct FlowGraph {
     num_vert: usize,
     edges: Vec<FlowEdge>,
     distance: Vec<i64>,
+    // lower bound of each edge, negated on residual edges
+    lower: Vec<i64>,
+    // required net inflow of each vertex, after subtracting lower bounds
+    need: Vec<i64>,
 }
 
 impl FlowGraph {
@@ -28,6 +32,8 @@ impl FlowGraph {
             num_vert: vmax,
             edges: Vec::with_capacity(2*emax_hint),
             distance: vec![],
+            lower: Vec::with_capacity(2*emax_hint),
+            need: vec![0; vmax],
         }
     }
 
@@ -49,9 +55,22 @@ impl FlowGraph {
         // add a residual edge
         self.adj[v].push((edge_id+1,u));
         self.edges.push(FlowEdge { v:u, u:v, cap:rcap, flow:0, cost: -cost });
+        self.lower.extend_from_slice(&[0, 0]);
         return (edge_id,edge_id+1);
     }
 
+    // Removes the vertices and edges added after the first num_v and num_e,
+    // which come last in every adjacency list.
+    fn truncate(&mut self, num_v: usize, num_e: usize) {
+        while self.num_e() > num_e {
+            let e = self.edges.pop().unwrap();
+            self.lower.pop();
+            self.adj[e.u].pop();
+        }
+        self.adj.truncate(num_v);
+        self.num_vert = num_v;
+    }
+
     /// Adds an edge with rcap == 0.
     pub fn add_edge(&mut self, u: usize, v: usize, cap: i64, cost:i64) -> (usize,usize) {
        return self.add_edge_rcap(u,v,cap,0,cost);
@@ -64,6 +83,123 @@ impl FlowGraph {
         return self.add_flow_edge(u,v,cap,rcap,cost);
     }
 
+    /// Adds an edge whose flow must lie between lower and upper, with
+    /// rcap == 0. Lower bounds are enforced by mcf_circulation() and the
+    /// *_with_bounds() methods, but ignored by mcf(); the flow field of the
+    /// edges doesn't include them.
+    /// Returns the IDs of the added edge and residual edge.
+    pub fn add_edge_bounds(&mut self, u: usize, v: usize, lower: i64, upper: i64, cost: i64) -> (usize,usize) {
+        assert!(0 <= lower && lower <= upper, "invalid bounds [{}, {}]", lower, upper);
+        let (e, re) = self.add_flow_edge(u,v,upper-lower,0,cost);
+        self.lower[e] = lower;
+        self.lower[re] = -lower;
+        self.need[u] += lower;
+        self.need[v] -= lower;
+        (e, re)
+    }
+
+    /// Requires the net inflow into v to be d, i.e., v consumes d units of
+    /// flow, or produces -d units if d is negative. Like lower bounds,
+    /// demands are only enforced by mcf_circulation() and the
+    /// *_with_bounds() methods.
+    pub fn add_demand(&mut self, v: usize, d: i64) {
+        self.need[v] += d;
+    }
+
+    /// Returns the flow on an edge, including its lower bound.
+    pub fn flow(&self, e: usize) -> i64 {
+        self.edges[e].flow + self.lower[e]
+    }
+
+    // Returns the total cost of the flow, including lower bounds.
+    fn total_cost(&self) -> i64 {
+        (0..self.num_e()).step_by(2).map(|e| self.flow(e) * self.edges[e].cost).sum()
+    }
+
+    // From the current flow, routes the remaining needs at minimum cost from
+    // a super source to a super sink, added temporarily, and returns whether
+    // they're all met.
+    fn satisfy_bounds(&mut self, need: &[i64]) -> bool {
+        let (n, m) = (self.num_v(), self.num_e());
+        if need.iter().sum::<i64>() != 0 {
+            return false;
+        }
+        self.adj.resize(n + 2, vec![]);
+        self.num_vert = n + 2;
+        let mut total = 0;
+        for (v, &d) in need.iter().enumerate() {
+            if d > 0 {
+                self.add_flow_edge(v, n + 1, d, 0, 0);
+                total += d;
+            } else if d < 0 {
+                self.add_flow_edge(n, v, -d, 0, 0);
+            }
+        }
+        let (_, flow) = self.mcf(n, n + 1);
+        self.truncate(n, m);
+        flow == total
+    }
+
+    /// Finds a minimum-cost flow satisfying all lower bounds, capacities and
+    /// demands, where every other vertex conserves flow. Returns its cost,
+    /// lower bounds included, or None if there's no feasible flow. Previous
+    /// flow values are cleared.
+    ///
+    /// Negative-cost cycles are allowed, as long as edges of negative cost
+    /// have finite capacities: saturating them leaves only non-negative
+    /// costs in the residual graph, and the resulting excesses are then
+    /// routed back at minimum cost.
+    pub fn mcf_circulation(&mut self) -> Option<i64> {
+        self.clear_flow();
+        let mut need = self.need.clone();
+        for e in 0..self.num_e() {
+            let edge = self.edges[e];
+            if edge.cost < 0 && edge.flow < edge.cap {
+                self.augment_path(e, edge.cap - edge.flow);
+                need[edge.u] += edge.cap - edge.flow;
+                need[edge.v] -= edge.cap - edge.flow;
+            }
+        }
+        if self.satisfy_bounds(&need) { Some(self.total_cost()) } else { None }
+    }
+
+    // Finds a feasible flow with s and t exempt from conservation, of
+    // minimum cost for its s-t value, and returns that value, which may be
+    // negative.
+    fn feasible_st_flow(&mut self, s: usize, t: usize) -> Option<i64> {
+        self.clear_flow();
+        let (n, m) = (self.num_v(), self.num_e());
+        // Temporary edges between s and t exempt them from conservation.
+        // Their cost exceeds that of any simple path, to avoid creating
+        // negative-cost cycles: the feasible flow found has minimum cost
+        // for its value, which the final augmentations preserve.
+        let penalty = 1 + self.edges.iter().step_by(2).map(|e| e.cost.abs()).sum::<i64>();
+        let (ts, _) = self.add_flow_edge(t,s,Self::INF/2,0,penalty);
+        let (st, _) = self.add_flow_edge(s,t,Self::INF/2,0,penalty);
+        let feasible = self.satisfy_bounds(&self.need.clone());
+        let value = self.edges[ts].flow - self.edges[st].flow;
+        self.truncate(n, m);
+        if feasible { Some(value) } else { None }
+    }
+
+    /// Among all maximum flows from s to t satisfying all lower bounds,
+    /// capacities and demands, where vertices other than s and t conserve
+    /// flow, finds one with minimum cost, assuming no negative-cost cycles.
+    /// Returns its cost, lower bounds included, and value, or None if there's
+    /// no feasible flow. Per-edge flows are available through flow().
+    pub fn mcf_with_bounds(&mut self, s: usize, t: usize) -> Option<(i64, i64)> {
+        let value = self.feasible_st_flow(s, t)?;
+        let (_, flow) = self.mcf(s, t);
+        Some((self.total_cost(), value + flow))
+    }
+
+    /// Like mcf_with_bounds(), but among all minimum flows from s to t.
+    pub fn mcf_min_flow_with_bounds(&mut self, s: usize, t: usize) -> Option<(i64, i64)> {
+        let value = self.feasible_st_flow(s, t)?;
+        let (_, flow) = self.mcf(t, s);
+        Some((self.total_cost(), value - flow))
+    }
+
     /// Gets an edge by the edge id.
     pub fn get_edge(&self, i: usize) -> &FlowEdge{
         return &self.edges[i];
@@ -102,20 +238,36 @@ impl FlowGraph {
     }
 
     /// Finds minimum cost flow with a flow limitation.
-    pub fn mcf_flow_limit(&mut self, s: usize, t: usize, mut flow_limit: i64) -> (i64, i64) {
+    pub fn mcf_flow_limit(&mut self, s: usize, t: usize, flow_limit: i64) -> (i64, i64) {
+        let &(max_flow, min_cost) = self.mcf_slope_limit(s, t, flow_limit).last().unwrap();
+        (min_cost, max_flow)
+    }
+
+    /// Like mcf(), but returns the minimum cost as a function of the flow
+    /// value, up to the maximum flow: a convex piecewise linear function,
+    /// given by its breakpoints (flow, cost), starting at (0, 0), with
+    /// strictly increasing slopes.
+    pub fn mcf_slope(&mut self, s: usize, t: usize) -> Vec<(i64, i64)> {
+        self.mcf_slope_limit(s, t, i64::MAX)
+    }
+
+    /// Like mcf_slope(), with a flow limitation.
+    pub fn mcf_slope_limit(&mut self, s: usize, t: usize, mut flow_limit: i64) -> Vec<(i64, i64)> {
         let mut pot = vec![0; self.num_v()];
 
         // Bellman-Ford deals with negative-cost edges at initialization.
         for _ in 1..self.num_v() {
             for e in 0..self.num_e() {
                 let edge = &self.edges[e];
-                if edge.cap > 0 {
+                if edge.flow < edge.cap {
                     pot[edge.v] = pot[edge.v].min(pot[edge.u] + edge.cost);
                 }
             }
         }
 
         let (mut min_cost, mut max_flow) = (0, 0);
+        let mut slope = vec![(0, 0)];
+        let mut prev_unit_cost = None;
         loop {
             if flow_limit <= 0 {
                 break;
@@ -128,8 +280,15 @@ impl FlowGraph {
             min_cost += dc;
             max_flow += df;
             flow_limit-=df;
+            // paths of equal cost extend the same linear piece
+            let unit_cost = Some(self.distance[t]);
+            if prev_unit_cost == unit_cost {
+                slope.pop();
+            }
+            slope.push((max_flow, min_cost));
+            prev_unit_cost = unit_cost;
         }
-        (min_cost, max_flow)
+        slope
     }
 
     // Maintains Johnson's potentials to prevent negative-cost residual edges.
@@ -184,6 +343,35 @@ impl FlowGraph {
     }
 }
 
+// Enumerates all flows on the edges (u, v, lower, upper, cost) within their
+// bounds, and returns the net inflow of each vertex and the total cost of
+// each one. Shared by the tests of FlowGraph and Dinic.
+#[cfg(test)]
+pub(crate) fn brute_force_flows(n: usize, edges: &[(usize, usize, i64, i64, i64)]) -> Vec<(Vec<i64>, i64)> {
+    let mut res = vec![];
+    let mut flows = edges.iter().map(|e| e.2).collect::<Vec<_>>();
+    loop {
+        let mut net = vec![0; n];
+        let mut cost = 0;
+        for (&(u, v, _, _, c), &f) in edges.iter().zip(&flows) {
+            net[u] -= f;
+            net[v] += f;
+            cost += f * c;
+        }
+        res.push((net, cost));
+        // next combination, like an odometer
+        let mut i = 0;
+        while i < edges.len() && flows[i] == edges[i].3 {
+            flows[i] = edges[i].2;
+            i += 1;
+        }
+        if i == edges.len() {
+            return res;
+        }
+        flows[i] += 1;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -202,6 +390,196 @@ mod test {
         assert_eq!(flow, 10);
     }
 
+    #[test]
+    fn test_mcf_bounds() {
+        let mut graph = FlowGraph::new(4, 5);
+        let e01 = graph.add_edge_bounds(0, 1, 1, 3, 2).0;
+        let e02 = graph.add_edge_bounds(0, 2, 0, 2, 1).0;
+        let e13 = graph.add_edge_bounds(1, 3, 0, 3, 1).0;
+        let e23 = graph.add_edge_bounds(2, 3, 2, 2, 5).0;
+        // flow 0 -> 3 is 1 + 2 at least, then up to 3 + 2
+        assert_eq!(graph.mcf_with_bounds(0, 3), Some((3 * 3 + 2 * 6, 5)));
+        assert_eq!((graph.flow(e01), graph.flow(e02)), (3, 2));
+        assert_eq!((graph.flow(e13), graph.flow(e23)), (3, 2));
+        assert_eq!(graph.mcf_min_flow_with_bounds(0, 3), Some((2 + 1 + 2 * 6, 3)));
+        assert_eq!((graph.flow(e01), graph.flow(e13)), (1, 1));
+
+        // a cheap edge back to the source makes the lower bounds circulate
+        let e30 = graph.add_edge_bounds(3, 0, 0, 9, -1).0;
+        assert_eq!(graph.mcf_circulation(), Some(3 + 12 - 3));
+        assert_eq!(graph.flow(e30), 3);
+        assert_eq!(graph.num_e(), 10);
+
+        // s -> t flows may be negative
+        let mut graph = FlowGraph::new(2, 1);
+        graph.add_edge_bounds(1, 0, 2, 4, -3);
+        assert_eq!(graph.mcf_with_bounds(0, 1), Some((-6, -2)));
+    }
+
+    #[test]
+    fn test_mcf_bounds_random() {
+        let mut rng = crate::rng::SmallRng::new(27);
+        for _ in 0..300 {
+            let n = 2 + rng.next_u64() as usize % 4;
+            let edges = (0..rng.next_u64() % 7)
+                .map(|_| {
+                    let lower = (rng.next_u64() % 3) as i64;
+                    let u = rng.next_u64() as usize % n;
+                    let v = (u + 1 + rng.next_u64() as usize % (n - 1)) % n;
+                    (u, v, lower, lower + (rng.next_u64() % 3) as i64, (rng.next_u64() % 10) as i64)
+                })
+                .collect::<Vec<_>>();
+            let mut graph = FlowGraph::new(n, edges.len());
+            for &(u, v, l, r, c) in &edges {
+                graph.add_edge_bounds(u, v, l, r, c);
+            }
+
+            // enumerates all flows within the bounds, keeping the best
+            // (value, -cost) for s -> t flows, the smallest (value, cost),
+            // and the cheapest circulation
+            let (s, t) = (0, n - 1);
+            let mut best_st: Option<(i64, i64)> = None;
+            let mut min_st: Option<(i64, i64)> = None;
+            let mut best_circ: Option<i64> = None;
+            for (net, cost) in brute_force_flows(n, &edges) {
+                if (0..n).all(|v| v == s || v == t || net[v] == 0) {
+                    best_st = best_st.max(Some((net[t], -cost)));
+                    min_st = Some(min_st.map_or((net[t], cost), |b| b.min((net[t], cost))));
+                    if net[t] == 0 {
+                        best_circ = Some(best_circ.map_or(cost, |b| b.min(cost)));
+                    }
+                }
+            }
+
+            let check = |graph: &FlowGraph, conserve_st: bool| {
+                let mut net = vec![0; n];
+                for (i, &(u, v, l, r, _)) in edges.iter().enumerate() {
+                    let f = graph.flow(2 * i);
+                    assert!(l <= f && f <= r);
+                    net[u] -= f;
+                    net[v] += f;
+                }
+                assert!((0..n).all(|v| net[v] == 0 || (!conserve_st && (v == s || v == t))));
+            };
+            let res = graph.mcf_with_bounds(s, t);
+            assert_eq!(res, best_st.map(|(value, cost)| (-cost, value)));
+            if res.is_some() {
+                check(&graph, false);
+            }
+            let res = graph.mcf_min_flow_with_bounds(s, t);
+            assert_eq!(res, min_st.map(|(value, cost)| (cost, value)));
+            if res.is_some() {
+                check(&graph, false);
+            }
+            let res = graph.mcf_circulation();
+            assert_eq!(res, best_circ);
+            if res.is_some() {
+                check(&graph, true);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mcf_slope() {
+        let mut graph = FlowGraph::new(4, 5);
+        graph.add_edge(0, 1, 2, 1);
+        graph.add_edge(0, 2, 3, 2);
+        graph.add_edge(1, 3, 3, 1);
+        graph.add_edge(2, 3, 2, 0);
+        graph.add_edge(1, 2, 1, -1);
+        // unit costs 0 by 0 -> 1 -> 2 -> 3, then 2 by 0 -> 1 -> 3 and
+        // 0 -> 2 -> 3, then 4 by 0 -> 2 -> 1 -> 3, undoing 1 -> 2
+        assert_eq!(graph.mcf_slope(0, 3), vec![(0, 0), (1, 0), (3, 4), (4, 8)]);
+
+        graph.clear_flow();
+        assert_eq!(graph.mcf_flow_limit(0, 3, 3), (4, 3));
+    }
+
+    #[test]
+    fn test_mcf_slope_random() {
+        let mut rng = crate::rng::SmallRng::new(29);
+        for _ in 0..100 {
+            let n = 2 + rng.next_u64() as usize % 5;
+            let edges = (0..rng.next_u64() % 10)
+                .map(|_| {
+                    let u = rng.next_u64() as usize % n;
+                    let v = (u + 1 + rng.next_u64() as usize % (n - 1)) % n;
+                    (u, v, (rng.next_u64() % 4) as i64, (rng.next_u64() % 10) as i64)
+                })
+                .collect::<Vec<_>>();
+            let build = || {
+                let mut graph = FlowGraph::new(n, edges.len());
+                for &(u, v, cap, cost) in &edges {
+                    graph.add_edge(u, v, cap, cost);
+                }
+                graph
+            };
+            let slope = build().mcf_slope(0, n - 1);
+            assert_eq!(slope[0], (0, 0));
+            for w in slope.windows(3) {
+                let (d1, d2) = ((w[1].0 - w[0].0, w[1].1 - w[0].1), (w[2].0 - w[1].0, w[2].1 - w[1].1));
+                assert!(d1.1 * d2.0 < d2.1 * d1.0);
+            }
+            let &(max_flow, _) = slope.last().unwrap();
+            for k in 0..=max_flow {
+                let (cost, flow) = build().mcf_flow_limit(0, n - 1, k);
+                assert_eq!(flow, k);
+                let i = slope.iter().position(|p| p.0 >= k).unwrap();
+                let expected = if slope[i].0 == k {
+                    slope[i].1
+                } else {
+                    let (a, b) = (slope[i - 1], slope[i]);
+                    a.1 + (b.1 - a.1) / (b.0 - a.0) * (k - a.0)
+                };
+                assert_eq!(cost, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mcf_negative_cycles() {
+        // negative cycles 0 -> 1 -> 0 and 0 -> 1 -> 2 -> 0, cheaper first
+        let mut graph = FlowGraph::new(3, 4);
+        let e01 = graph.add_edge(0, 1, 4, -3).0;
+        let e12 = graph.add_edge(1, 2, 3, 1).0;
+        let e20 = graph.add_edge(2, 0, 5, 1).0;
+        let e10 = graph.add_edge(1, 0, 2, 1).0;
+        assert_eq!(graph.mcf_circulation(), Some(-12 + 2 + 2 + 2));
+        assert_eq!((graph.flow(e01), graph.flow(e12), graph.flow(e20), graph.flow(e10)), (4, 2, 2, 2));
+
+        let mut rng = crate::rng::SmallRng::new(30);
+        for _ in 0..300 {
+            let n = 2 + rng.next_u64() as usize % 4;
+            let edges = (0..rng.next_u64() % 7)
+                .map(|_| {
+                    let lower = (rng.next_u64() % 2) as i64;
+                    let u = rng.next_u64() as usize % n;
+                    let v = (u + 1 + rng.next_u64() as usize % (n - 1)) % n;
+                    (u, v, lower, lower + (rng.next_u64() % 4) as i64, (rng.next_u64() % 11) as i64 - 5)
+                })
+                .collect::<Vec<_>>();
+            let mut graph = FlowGraph::new(n, edges.len());
+            for &(u, v, l, r, c) in &edges {
+                graph.add_edge_bounds(u, v, l, r, c);
+            }
+            let demand = (rng.next_u64() % 3) as i64;
+            graph.add_demand(0, -demand);
+            graph.add_demand(n - 1, demand);
+
+            let best = brute_force_flows(n, &edges)
+                .into_iter()
+                .filter(|(net, _)| (0..n).all(|v| net[v] == if v == 0 { -demand } else if v == n - 1 { demand } else { 0 }))
+                .map(|(_, cost)| cost)
+                .min();
+            assert_eq!(graph.mcf_circulation(), best);
+            if best.is_some() {
+                let cost = edges.iter().enumerate().map(|(i, e)| graph.flow(2 * i) * e.4).sum::<i64>();
+                assert_eq!(Some(cost), best);
+                assert!(edges.iter().enumerate().all(|(i, e)| e.2 <= graph.flow(2 * i) && graph.flow(2 * i) <= e.3));
+            }
+        }
+    }
+
     #[test]
     // https://atcoder.jp/contests/practice2/tasks/practice2_e
     // https://atcoder.github.io/ac-library/production/document_en/mincostflow.html
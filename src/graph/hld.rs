@@ -0,0 +1,338 @@
+//! Heavy-Light Decomposition, for path and subtree queries on a static tree
+use super::{Edge, Graph};
+use crate::range_query::{ArqSpec, StaticArq};
+
+// Ranges of positions going up from one end of a path, and those going down
+// to the other end in reverse order.
+type PathRanges = (Vec<(usize, usize)>, Vec<(usize, usize)>);
+
+/// A rooted tree with values on its vertices, supporting queries and updates
+/// on paths and subtrees. The vertices are laid out so that every subtree,
+/// as well as every path, is made of O(log n) contiguous ranges, which are
+/// stored in a StaticArq. Path aggregates are taken in order from one end to
+/// the other, so op() need not be commutative; to this end, a second
+/// StaticArq stores the values in reverse.
+///
+/// Values on edges are supported by storing each edge's value on its lower
+/// endpoint; see query_path_edges() and update_path_edges().
+///
+/// # Panics
+///
+/// Methods will panic if given an out-of-bounds vertex.
+pub struct Hld<T: ArqSpec> {
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    head: Vec<usize>,
+    pos: Vec<usize>,
+    arq: StaticArq<T>,
+    rev_arq: StaticArq<T>,
+}
+
+impl<T: ArqSpec> Hld<T> {
+    /// Decomposes the tree given by its adjacency lists, rooted at root,
+    /// setting the value of each vertex v to init_val[v].
+    pub fn new(adj: &[Vec<usize>], root: usize, init_val: &[T::S]) -> Self {
+        let n = adj.len();
+        let mut parent = vec![usize::MAX; n];
+        let mut depth = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        while let Some(u) = stack.pop() {
+            order.push(u);
+            for &v in &adj[u] {
+                if v != parent[u] {
+                    parent[v] = u;
+                    depth[v] = depth[u] + 1;
+                    stack.push(v);
+                }
+            }
+        }
+
+        let mut size = vec![1; n];
+        let mut heavy = vec![usize::MAX; n];
+        for &u in order.iter().rev() {
+            if u != root {
+                size[parent[u]] += size[u];
+            }
+        }
+        for &u in &order {
+            heavy[u] = adj[u]
+                .iter()
+                .copied()
+                .filter(|&v| v != parent[u])
+                .max_by_key(|&v| size[v])
+                .unwrap_or(usize::MAX);
+        }
+
+        // Each chain is laid out from top to bottom, followed by the subtrees
+        // hanging off it, so that every subtree is laid out contiguously.
+        let mut head = vec![0; n];
+        let mut pos = vec![0; n];
+        let mut next_pos = 0;
+        let mut stack = vec![root];
+        while let Some(top) = stack.pop() {
+            let mut u = top;
+            while u != usize::MAX {
+                head[u] = top;
+                pos[u] = next_pos;
+                next_pos += 1;
+                for &v in &adj[u] {
+                    if v != parent[u] && v != heavy[u] {
+                        stack.push(v);
+                    }
+                }
+                u = heavy[u];
+            }
+        }
+
+        let mut vals = vec![T::identity(); n];
+        for (u, val) in init_val.iter().enumerate() {
+            vals[pos[u]] = val.clone();
+        }
+        let arq = StaticArq::new(&vals);
+        vals.reverse();
+        let rev_arq = StaticArq::new(&vals);
+        Self {
+            parent,
+            depth,
+            size,
+            head,
+            pos,
+            arq,
+            rev_arq,
+        }
+    }
+
+    /// Decomposes an undirected tree; see new().
+    pub fn from_graph(graph: &Graph<Edge>, root: usize, init_val: &[T::S]) -> Self {
+        let adj = (0..graph.num_v())
+            .map(|u| graph.adj_list(u).iter().map(|a| a.v).collect())
+            .collect::<Vec<Vec<_>>>();
+        Self::new(&adj, root, init_val)
+    }
+
+    /// Returns the parent of u, if any.
+    pub fn parent(&self, u: usize) -> Option<usize> {
+        Some(self.parent[u]).filter(|&p| p != usize::MAX)
+    }
+
+    /// Returns the number of edges between u and the root.
+    pub fn depth(&self, u: usize) -> usize {
+        self.depth[u]
+    }
+
+    /// Returns the index of u in the underlying StaticArq.
+    pub fn pos(&self, u: usize) -> usize {
+        self.pos[u]
+    }
+
+    /// Returns the lowest common ancestor of u and v.
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            u = self.parent[self.head[u]];
+        }
+        if self.depth[u] < self.depth[v] {
+            u
+        } else {
+            v
+        }
+    }
+
+    // Splits the path from u to v into ranges of positions. If skip_lca, the
+    // lowest common ancestor is left out.
+    fn path_ranges(&self, mut u: usize, mut v: usize, skip_lca: bool) -> PathRanges {
+        let (mut up, mut down) = (vec![], vec![]);
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] >= self.depth[self.head[v]] {
+                up.push((self.pos[self.head[u]], self.pos[u]));
+                u = self.parent[self.head[u]];
+            } else {
+                down.push((self.pos[self.head[v]], self.pos[v]));
+                v = self.parent[self.head[v]];
+            }
+        }
+        let skip = skip_lca as usize;
+        if self.depth[u] >= self.depth[v] {
+            up.push((self.pos[v] + skip, self.pos[u]));
+        } else {
+            down.push((self.pos[u] + skip, self.pos[v]));
+        }
+        (up, down)
+    }
+
+    fn query_ranges(&mut self, (up, down): PathRanges) -> T::S {
+        let n = self.pos.len();
+        let mut agg = T::identity();
+        for (l, r) in up {
+            if l <= r {
+                agg = T::op(&agg, &self.rev_arq.query(n - 1 - r, n - 1 - l));
+            }
+        }
+        for (l, r) in down.into_iter().rev() {
+            agg = T::op(&agg, &self.arq.query(l, r));
+        }
+        agg
+    }
+
+    fn update_ranges(&mut self, (up, down): PathRanges, f: &T::F) {
+        let n = self.pos.len();
+        for (l, r) in up.into_iter().chain(down) {
+            if l <= r {
+                self.arq.update(l, r, f);
+                self.rev_arq.update(n - 1 - r, n - 1 - l, f);
+            }
+        }
+    }
+
+    /// Returns the aggregate of the values on the path from u to v, in order.
+    pub fn query_path(&mut self, u: usize, v: usize) -> T::S {
+        let ranges = self.path_ranges(u, v, false);
+        self.query_ranges(ranges)
+    }
+
+    /// Applies the endomorphism f to the values on the path from u to v.
+    pub fn update_path(&mut self, u: usize, v: usize, f: &T::F) {
+        let ranges = self.path_ranges(u, v, false);
+        self.update_ranges(ranges, f);
+    }
+
+    /// Returns the aggregate of the values on the edges of the path from u
+    /// to v, in order, where the value of an edge is kept on its lower
+    /// endpoint.
+    pub fn query_path_edges(&mut self, u: usize, v: usize) -> T::S {
+        let ranges = self.path_ranges(u, v, true);
+        self.query_ranges(ranges)
+    }
+
+    /// Applies the endomorphism f to the values on the edges of the path from
+    /// u to v; see query_path_edges().
+    pub fn update_path_edges(&mut self, u: usize, v: usize, f: &T::F) {
+        let ranges = self.path_ranges(u, v, true);
+        self.update_ranges(ranges, f);
+    }
+
+    /// Returns the aggregate of the values in the subtree of u, in the order
+    /// of the underlying StaticArq.
+    pub fn query_subtree(&mut self, u: usize) -> T::S {
+        self.arq.query(self.pos[u], self.pos[u] + self.size[u] - 1)
+    }
+
+    /// Applies the endomorphism f to the values in the subtree of u.
+    pub fn update_subtree(&mut self, u: usize, f: &T::F) {
+        let (l, r) = (self.pos[u], self.pos[u] + self.size[u] - 1);
+        let n = self.pos.len();
+        self.arq.update(l, r, f);
+        self.rev_arq.update(n - 1 - r, n - 1 - l, f);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::range_query::specs::{ArqAddSum, ArqPath};
+    use crate::rng::SmallRng;
+
+    fn naive_path(parent: &[usize], depth: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let (mut up, mut down) = (vec![], vec![]);
+        while depth[u] > depth[v] {
+            up.push(u);
+            u = parent[u];
+        }
+        while depth[v] > depth[u] {
+            down.push(v);
+            v = parent[v];
+        }
+        while u != v {
+            up.push(u);
+            down.push(v);
+            u = parent[u];
+            v = parent[v];
+        }
+        up.push(u);
+        up.extend(down.into_iter().rev());
+        up
+    }
+
+    #[test]
+    fn test_hld() {
+        let mut graph = Graph::new(6, 5);
+        for &(u, v) in &[(0, 1), (0, 2), (1, 3), (1, 4), (2, 5)] {
+            graph.add_undirected_edge(u, v);
+        }
+        let mut hld = Hld::<ArqAddSum>::from_graph(&graph, 0, &[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(hld.lca(3, 5), 0);
+        assert_eq!(hld.lca(3, 4), 1);
+        assert_eq!(hld.query_path(3, 5), 16);
+        assert_eq!(hld.query_path_edges(3, 5), 15);
+        assert_eq!(hld.query_subtree(1), 11);
+        hld.update_subtree(1, &10);
+        assert_eq!(hld.query_path(4, 2), 31);
+        hld.update_path_edges(4, 2, &1);
+        assert_eq!(hld.query_path(0, 4), 30);
+        assert_eq!(hld.query_subtree(0), 54);
+    }
+
+    #[test]
+    fn test_hld_random() {
+        let mut rng = SmallRng::new(14);
+        let n = 80;
+        let mut adj = vec![vec![]; n];
+        let (mut parent, mut depth) = (vec![usize::MAX; n], vec![0; n]);
+        for v in 1..n {
+            let p = rng.next_u64() as usize % v;
+            parent[v] = p;
+            depth[v] = depth[p] + 1;
+            adj[p].push(v);
+            adj[v].push(p);
+        }
+        let mut vals = (0..n).map(|_| (rng.next_u64() % 100) as i64).collect::<Vec<_>>();
+        let mut hld = Hld::<ArqAddSum>::new(&adj, 0, &vals);
+        let ids = (0..n).map(|v| vec![v]).collect::<Vec<_>>();
+        let mut path_hld = Hld::<ArqPath>::new(&adj, 0, &ids);
+
+        for _ in 0..1000 {
+            let u = rng.next_u64() as usize % n;
+            let v = rng.next_u64() as usize % n;
+            let path = naive_path(&parent, &depth, u, v);
+            let lca = *path.iter().min_by_key(|&&w| depth[w]).unwrap();
+            assert_eq!(hld.lca(u, v), lca);
+            assert_eq!(path_hld.query_path(u, v), path);
+            let x = (rng.next_u64() % 10) as i64;
+            match rng.next_u64() % 4 {
+                0 => {
+                    hld.update_path(u, v, &x);
+                    path.iter().for_each(|&w| vals[w] += x);
+                }
+                1 => {
+                    hld.update_path_edges(u, v, &x);
+                    path.iter().filter(|&&w| w != lca).for_each(|&w| vals[w] += x);
+                }
+                2 => {
+                    hld.update_subtree(u, &x);
+                    for w in 0..n {
+                        if naive_path(&parent, &depth, u, w).iter().all(|&y| depth[y] >= depth[u]) {
+                            vals[w] += x;
+                        }
+                    }
+                }
+                _ => {
+                    let subtree = (0..n)
+                        .filter(|&w| naive_path(&parent, &depth, u, w).iter().all(|&y| depth[y] >= depth[u]))
+                        .map(|w| vals[w])
+                        .sum::<i64>();
+                    assert_eq!(hld.query_subtree(u), subtree);
+                    assert_eq!(hld.query_path(u, v), path.iter().map(|&w| vals[w]).sum::<i64>());
+                    assert_eq!(
+                        hld.query_path_edges(u, v),
+                        path.iter().filter(|&&w| w != lca).map(|&w| vals[w]).sum::<i64>()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Weighted bipartite assignment by the Hungarian algorithm
+
+/// Hungarian algorithm for the assignment problem: given an n x m cost
+/// matrix with n <= m, assigns each row a distinct column so as to minimize
+/// the total cost. Returns the total and the column assigned to each row.
+/// Runs in O(n^2 m) time, maintaining dual potentials on rows and columns
+/// and adding one row at a time along a shortest augmenting path.
+///
+/// # Panics
+///
+/// Panics if there are more rows than columns.
+pub fn min_cost_assignment(cost: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let n = cost.len();
+    let m = cost.first().map_or(0, |row| row.len());
+    assert!(n <= m, "{} rows can't be assigned to {} columns", n, m);
+    // Rows and columns are 1-indexed, column 0 holding the row being added.
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; m + 1];
+    let mut row_of = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![i64::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let cur = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                    if cur < min_v[j] {
+                        min_v[j] = cur;
+                        way[j] = j0;
+                    }
+                    if min_v[j] < delta {
+                        delta = min_v[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=m {
+        if row_of[j] > 0 {
+            assignment[row_of[j] - 1] = j - 1;
+        }
+    }
+    let total = (0..n).map(|i| cost[i][assignment[i]]).sum();
+    (total, assignment)
+}
+
+/// Assigns each row of an n x m weight matrix, n <= m, a distinct column
+/// so as to maximize the total weight. Returns the total and the column
+/// assigned to each row.
+///
+/// # Panics
+///
+/// Panics if there are more rows than columns.
+pub fn max_weight_assignment(weight: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let cost = weight.iter().map(|row| row.iter().map(|&w| -w).collect()).collect::<Vec<_>>();
+    let (total, assignment) = min_cost_assignment(&cost);
+    (-total, assignment)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_hungarian() {
+        let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(min_cost_assignment(&cost), (5, vec![1, 0, 2]));
+        assert_eq!(max_weight_assignment(&cost), (11, vec![0, 2, 1]));
+
+        let cost = vec![vec![7, 3, 9, 1], vec![8, 2, 9, 6]];
+        assert_eq!(min_cost_assignment(&cost), (3, vec![3, 1]));
+        assert_eq!(min_cost_assignment(&[]), (0, vec![]));
+    }
+
+    // tries every injective assignment of rows to columns
+    fn brute_force(cost: &[Vec<i64>], row: usize, used: &mut Vec<bool>) -> i64 {
+        if row == cost.len() {
+            return 0;
+        }
+        let mut best = i64::MAX;
+        for j in 0..used.len() {
+            if !used[j] {
+                used[j] = true;
+                best = best.min(cost[row][j] + brute_force(cost, row + 1, used));
+                used[j] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_hungarian_random() {
+        let mut rng = SmallRng::new(25);
+        for _ in 0..200 {
+            let n = 1 + rng.next_u64() as usize % 6;
+            let m = n + rng.next_u64() as usize % 3;
+            let cost = (0..n)
+                .map(|_| (0..m).map(|_| (rng.next_u64() % 2_000_000_001) as i64 - 1_000_000_000).collect())
+                .collect::<Vec<Vec<_>>>();
+            let (total, assignment) = min_cost_assignment(&cost);
+            assert_eq!(total, brute_force(&cost, 0, &mut vec![false; m]));
+            let mut cols = assignment.clone();
+            cols.sort_unstable();
+            cols.dedup();
+            assert_eq!(cols.len(), n);
+
+            let neg = cost.iter().map(|row| row.iter().map(|&c| -c).collect()).collect::<Vec<Vec<_>>>();
+            assert_eq!(max_weight_assignment(&cost).0, -brute_force(&neg, 0, &mut vec![false; m]));
+        }
+    }
+}
@@ -1,5 +1,13 @@
+/// Lowest common ancestors and related queries on a rooted tree, by binary
+/// lifting: O(n log n) preprocessing and O(log n) time per query.
+///
+/// The tree is built iteratively, so path-shaped trees of any size are fine.
+/// Vertices outside the root's component are left unvisited.
 pub struct Lca {
+    /// dp[v][i] is the 2^i-th ancestor of v, or n if there is none
     pub dp: Vec<Vec<usize>>,
+    tin: Vec<usize>,
+    tout: Vec<usize>,
     pub dist: Vec<usize>,
     pub n: usize,
     pub m: usize,
@@ -9,55 +17,164 @@ impl Lca {
     pub fn new(n:usize) -> Self {
         let mut m = 0;
         while (n>>m) > 0 { m+=1; }
-        return Self { dp: vec![vec![n;m];n+1], dist: vec![0;n], n, m };
+        Self { dp: vec![vec![n;m];n+1], tin: vec![0; n], tout: vec![0; n], dist: vec![0;n], n, m }
     }
-    pub fn init(&mut self, adj: &Vec<Vec<usize>>) {
-        self.dfs(adj,0,self.n);
+    pub fn init(&mut self, adj: &[Vec<usize>]) {
+        self.init_from(adj, 0);
     }
-    pub fn init_from(&mut self, adj: &Vec<Vec<usize>>, root:usize) {
-        self.dfs(adj,root,self.n);
-    }
-    fn dfs(&mut self, adj: &Vec<Vec<usize>>, u: usize, p: usize) {
-        self.dp[u][0] = p;
-        for i in 1..self.m {
-            self.dp[u][i] = self.dp[self.dp[u][i-1]][i-1];
+    pub fn init_from(&mut self, adj: &[Vec<usize>], root:usize) {
+        let n = self.n;
+        let mut timer = 0;
+        self.dp[root][0] = n;
+        self.dist[root] = 0;
+        self.tin[root] = timer;
+        timer += 1;
+        let mut stack = vec![(root, 0)];
+        while let Some((u, i)) = stack.pop() {
+            match adj[u].get(i) {
+                Some(&v) => {
+                    stack.push((u, i + 1));
+                    if v == self.dp[u][0] { continue; }
+                    self.dp[v][0] = u;
+                    self.dist[v] = self.dist[u] + 1;
+                    self.tin[v] = timer;
+                    timer += 1;
+                    stack.push((v, 0));
+                }
+                None => self.tout[u] = timer,
+            }
         }
-        for &v in &adj[u] {
-            if v == p { continue; }
-            self.dist[v] = self.dist[u]+1;
-            self.dfs(adj,v,u);
+        for i in 1..self.m {
+            for v in 0..=n {
+                self.dp[v][i] = self.dp[self.dp[v][i-1]][i-1];
+            }
         }
     }
-    pub fn lca(&self, mut u:usize, mut v:usize) -> usize {
-        if self.dist[u]>self.dist[v] { std::mem::swap(&mut u, &mut v) }
-        let d = self.dist[v]-self.dist[u];
+    fn ancestor(&self, i: usize, v: usize) -> usize {
+        self.dp[v][i]
+    }
+    /// Returns whether u is an ancestor of v, including u == v.
+    pub fn is_ancestor(&self, u:usize, v:usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tout[v] <= self.tout[u]
+    }
+    /// Returns the k-th ancestor of v, where the 0-th is v itself, if any.
+    pub fn kth_ancestor(&self, mut v:usize, k:usize) -> Option<usize> {
+        if k > self.dist[v] { return None; }
         for i in 0..self.m {
-            if d>>i&1 == 1 {
-                v = self.dp[v][i];
+            if k>>i&1 == 1 {
+                v = self.ancestor(i, v);
             }
         }
+        Some(v)
+    }
+    pub fn lca(&self, mut u:usize, mut v:usize) -> usize {
+        if self.dist[u]>self.dist[v] { std::mem::swap(&mut u, &mut v) }
+        v = self.kth_ancestor(v, self.dist[v]-self.dist[u]).unwrap();
         if u == v {
             return u;
         }
         for i in (0..self.m).rev() {
-            if self.dp[u][i] != self.dp[v][i] {
-                u=self.dp[u][i];
-                v=self.dp[v][i];
+            if self.ancestor(i, u) != self.ancestor(i, v) {
+                u=self.ancestor(i, u);
+                v=self.ancestor(i, v);
             }
         }
-        return self.dp[u][0];
+        self.ancestor(0, u)
+    }
+    /// Returns the number of edges on the path between u and v.
+    pub fn dist(&self, u:usize, v:usize) -> usize {
+        self.dist[u]+self.dist[v]-2*self.dist[self.lca(u,v)]
     }
     pub fn len(&self, u:usize, v:usize) -> usize {
-        return self.dist[u]+self.dist[v]-2*self.dist[self.lca(u,v)];
+        self.dist(u, v)
     }
     pub fn is_between(&self, a:usize, u:usize, v:usize) -> bool {
-        return self.len(u,v) == self.len(u,a)+self.len(a,v);
+        self.dist(u,v) == self.dist(u,a)+self.dist(a,v)
+    }
+    /// Returns the k-th vertex on the path from u to v, where the 0-th is u,
+    /// or None if the path is shorter.
+    pub fn jump(&self, u:usize, v:usize, k:usize) -> Option<usize> {
+        let w = self.lca(u, v);
+        let (du, dv) = (self.dist[u]-self.dist[w], self.dist[v]-self.dist[w]);
+        if k <= du {
+            self.kth_ancestor(u, k)
+        } else if k <= du+dv {
+            self.kth_ancestor(v, du+dv-k)
+        } else {
+            None
+        }
+    }
+}
+
+/// Lowest common ancestors by range minimum queries on an Euler tour of the
+/// tree: O(n log n) preprocessing and O(1) time per query.
+pub struct EulerLca {
+    // table[i][j] is the shallowest vertex among tour[j..j + 2^i]
+    table: Vec<Vec<usize>>,
+    first: Vec<usize>,
+    pub dist: Vec<usize>,
+}
+
+impl EulerLca {
+    /// Builds the tour of the tree given by its adjacency lists, rooted at root.
+    pub fn new(adj: &[Vec<usize>], root: usize) -> Self {
+        let n = adj.len();
+        let mut dist = vec![0; n];
+        let mut first = vec![0; n];
+        let mut tour = Vec::with_capacity(2 * n);
+        let mut stack = vec![(root, n, 0)];
+        while let Some((u, p, i)) = stack.pop() {
+            if i == 0 {
+                first[u] = tour.len();
+            }
+            tour.push(u);
+            if let Some(j) = adj[u][i..].iter().position(|&v| v != p) {
+                let v = adj[u][i + j];
+                stack.push((u, p, i + j + 1));
+                dist[v] = dist[u] + 1;
+                stack.push((v, u, 0));
+            }
+        }
+
+        let mut table = vec![tour];
+        let mut w = 1;
+        while 2 * w <= table[0].len() {
+            let prev = table.last().unwrap();
+            let row = (0..prev.len() - w)
+                .map(|j| {
+                    let (a, b) = (prev[j], prev[j + w]);
+                    if dist[a] <= dist[b] { a } else { b }
+                })
+                .collect();
+            table.push(row);
+            w *= 2;
+        }
+        Self { table, first, dist }
+    }
+
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        let (l, r) = if self.first[u] <= self.first[v] {
+            (self.first[u], self.first[v] + 1)
+        } else {
+            (self.first[v], self.first[u] + 1)
+        };
+        // the floor of log2(r - l)
+        let i = ((std::mem::size_of::<usize>() * 8) as u32 - 1 - (r - l).leading_zeros()) as usize;
+        let (a, b) = (self.table[i][l], self.table[i][r - (1 << i)]);
+        if self.dist[a] <= self.dist[b] { a } else { b }
+    }
+
+    /// Returns the number of edges on the path between u and v.
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.dist[u] + self.dist[v] - 2 * self.dist[self.lca(u, v)]
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rng::SmallRng;
+
     #[test]
     fn test_lca() {
         let tree = vec![
@@ -105,5 +222,93 @@ mod test {
         assert_eq!(true,lca.is_between(6,17,12));
         assert_eq!(false,lca.is_between(8,17,12));
 
+        assert_eq!(Some(7),lca.kth_ancestor(17,2));
+        assert_eq!(None,lca.kth_ancestor(17,6));
+        assert_eq!(Some(17),lca.jump(17,12,0));
+        assert_eq!(Some(3),lca.jump(17,12,3));
+        assert_eq!(Some(9),lca.jump(17,12,5));
+        assert_eq!(None,lca.jump(17,12,7));
+        assert!(lca.is_ancestor(3,14));
+        assert!(!lca.is_ancestor(14,3));
+
+        // rerooting at 3 reaches 1's subtree through 3's old parent
+        lca.init_from(&adj, 3);
+        assert_eq!(3,lca.lca(5,14));
+        assert_eq!(1,lca.lca(5,18));
+        assert_eq!(Some(1),lca.kth_ancestor(18,2));
+        assert_eq!(None,lca.kth_ancestor(3,1));
+        assert_eq!(3,lca.dist(18,3));
+        lca.init(&adj);
+
+        let euler = EulerLca::new(&adj, 0);
+        assert_eq!(3,euler.lca(17,12));
+        assert_eq!(0,euler.lca(17,18));
+        assert_eq!(6,euler.dist(17,12));
+    }
+
+    #[test]
+    fn test_lca_random() {
+        let mut rng = SmallRng::new(16);
+        for &n in &[1, 2, 3, 10, 100] {
+            let mut adj = vec![Vec::new(); n];
+            let mut parent = vec![usize::MAX; n];
+            let mut depth = vec![0; n];
+            for v in 1..n {
+                let p = rng.next_u64() as usize % v;
+                parent[v] = p;
+                depth[v] = depth[p] + 1;
+                adj[p].push(v);
+                adj[v].push(p);
+            }
+            let ancestors = |mut v: usize| {
+                let mut res = vec![v];
+                while parent[v] != usize::MAX {
+                    v = parent[v];
+                    res.push(v);
+                }
+                res
+            };
+            let mut lca = Lca::new(n);
+            lca.init(&adj);
+            let euler = EulerLca::new(&adj, 0);
+            for u in 0..n {
+                let anc_u = ancestors(u);
+                for k in 0..=n {
+                    assert_eq!(lca.kth_ancestor(u, k), anc_u.get(k).copied());
+                }
+                for v in 0..n {
+                    let anc_v = ancestors(v);
+                    let w = *anc_u.iter().find(|x| anc_v.contains(x)).unwrap();
+                    assert_eq!(lca.lca(u, v), w);
+                    assert_eq!(euler.lca(u, v), w);
+                    assert_eq!(lca.is_ancestor(u, v), w == u);
+                    let mut path = anc_u[..=depth[u] - depth[w]].to_vec();
+                    path.extend(anc_v[..depth[v] - depth[w]].iter().rev());
+                    assert_eq!(lca.dist(u, v), path.len() - 1);
+                    for k in 0..=path.len() {
+                        assert_eq!(lca.jump(u, v, k), path.get(k).copied());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lca_long_path() {
+        let n = 1_000_000;
+        let mut adj = vec![Vec::new(); n];
+        for v in 1..n {
+            adj[v - 1].push(v);
+            adj[v].push(v - 1);
+        }
+        let mut lca = Lca::new(n);
+        lca.init(&adj);
+        assert_eq!(lca.lca(n - 1, 12345), 12345);
+        assert_eq!(lca.jump(0, n - 1, 777), Some(777));
+
+        adj.truncate(n / 10);
+        adj[n / 10 - 1].pop();
+        let euler = EulerLca::new(&adj, 0);
+        assert_eq!(euler.lca(n / 10 - 1, 12345), 12345);
     }
 }
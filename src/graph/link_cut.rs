@@ -0,0 +1,425 @@
+//! Link-Cut Tree, a dynamic forest supporting path queries and updates
+use crate::range_query::ArqSpec;
+
+const NIL: usize = usize::MAX;
+
+struct LinkCutNode<T: ArqSpec> {
+    val: T::S,
+    // aggregates of the splay subtree, in path order and in reverse
+    sum: T::S,
+    rev_sum: T::S,
+    app: Option<T::F>,
+    flip: bool,
+    size: i64,
+    down: [usize; 2],
+    up: usize,
+}
+
+/// A forest on vertices 0..n whose edges can be added and removed online,
+/// answering aggregate queries and applying updates on the path between two
+/// vertices, where the aggregate and update are given by an ArqSpec. The
+/// aggregate is taken in order along the path, so op() need not be
+/// commutative. Every operation takes O(log n) amortized time.
+///
+/// The forest is rooted, but evert() can move the root of a tree to any
+/// of its vertices.
+///
+/// # Panics
+///
+/// Methods will panic if given an out-of-bounds vertex.
+pub struct LinkCutTree<T: ArqSpec> {
+    nodes: Vec<LinkCutNode<T>>,
+}
+
+impl<T: ArqSpec> LinkCutTree<T> {
+    /// Initializes a forest of isolated vertices with the given values.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let nodes = init_val
+            .iter()
+            .map(|v| LinkCutNode {
+                val: v.clone(),
+                sum: v.clone(),
+                rev_sum: v.clone(),
+                app: None,
+                flip: false,
+                size: 1,
+                down: [NIL; 2],
+                up: NIL,
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if there are no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn is_splay_root(&self, x: usize) -> bool {
+        let p = self.nodes[x].up;
+        p == NIL || !self.nodes[p].down.contains(&x)
+    }
+
+    fn apply(&mut self, x: usize, f: &T::F) {
+        if x == NIL {
+            return;
+        }
+        let node = &mut self.nodes[x];
+        node.val = T::apply(f, &node.val, 1);
+        node.sum = T::apply(f, &node.sum, node.size);
+        node.rev_sum = T::apply(f, &node.rev_sum, node.size);
+        let h = match node.app {
+            Some(ref g) => T::compose(f, g),
+            None => f.clone(),
+        };
+        node.app = Some(h);
+    }
+
+    fn reverse(&mut self, x: usize) {
+        if x == NIL {
+            return;
+        }
+        let node = &mut self.nodes[x];
+        node.down.swap(0, 1);
+        std::mem::swap(&mut node.sum, &mut node.rev_sum);
+        node.flip ^= true;
+    }
+
+    fn push(&mut self, x: usize) {
+        let [l, r] = self.nodes[x].down;
+        if let Some(ref f) = self.nodes[x].app.take() {
+            self.apply(l, f);
+            self.apply(r, f);
+        }
+        if self.nodes[x].flip {
+            self.nodes[x].flip = false;
+            self.reverse(l);
+            self.reverse(r);
+        }
+    }
+
+    fn pull(&mut self, x: usize) {
+        let [l, r] = self.nodes[x].down;
+        let (mut sum, mut rev_sum, mut size) = (self.nodes[x].val.clone(), self.nodes[x].val.clone(), 1);
+        if l != NIL {
+            sum = T::op(&self.nodes[l].sum, &sum);
+            rev_sum = T::op(&rev_sum, &self.nodes[l].rev_sum);
+            size += self.nodes[l].size;
+        }
+        if r != NIL {
+            sum = T::op(&sum, &self.nodes[r].sum);
+            rev_sum = T::op(&self.nodes[r].rev_sum, &rev_sum);
+            size += self.nodes[r].size;
+        }
+        let node = &mut self.nodes[x];
+        node.sum = sum;
+        node.rev_sum = rev_sum;
+        node.size = size;
+    }
+
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].up;
+        let g = self.nodes[p].up;
+        let dir = (self.nodes[p].down[1] == x) as usize;
+        let c = self.nodes[x].down[dir ^ 1];
+        if !self.is_splay_root(p) {
+            let pdir = (self.nodes[g].down[1] == p) as usize;
+            self.nodes[g].down[pdir] = x;
+        }
+        self.nodes[x].up = g;
+        self.nodes[p].down[dir] = c;
+        if c != NIL {
+            self.nodes[c].up = p;
+        }
+        self.nodes[x].down[dir ^ 1] = p;
+        self.nodes[p].up = x;
+        self.pull(p);
+        self.pull(x);
+    }
+
+    fn splay(&mut self, x: usize) {
+        let mut path = vec![x];
+        while !self.is_splay_root(*path.last().unwrap()) {
+            path.push(self.nodes[*path.last().unwrap()].up);
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_splay_root(x) {
+            let p = self.nodes[x].up;
+            if !self.is_splay_root(p) {
+                let g = self.nodes[p].up;
+                let zig_zig = (self.nodes[p].down[1] == x) == (self.nodes[g].down[1] == p);
+                self.rotate(if zig_zig { p } else { x });
+            }
+            self.rotate(x);
+        }
+    }
+
+    // Makes the path from the root to x preferred, leaving x at the root of
+    // its splay tree with no right child. Returns the last vertex at which
+    // the path joined the previously preferred paths.
+    fn access(&mut self, x: usize) -> usize {
+        let (mut last, mut y) = (NIL, x);
+        while y != NIL {
+            self.splay(y);
+            self.nodes[y].down[1] = last;
+            self.pull(y);
+            last = y;
+            y = self.nodes[y].up;
+        }
+        self.splay(x);
+        last
+    }
+
+    /// Makes u the root of its tree.
+    pub fn evert(&mut self, u: usize) {
+        self.access(u);
+        self.reverse(u);
+    }
+
+    /// Returns the root of u's tree.
+    pub fn find_root(&mut self, u: usize) -> usize {
+        self.access(u);
+        let mut x = u;
+        loop {
+            self.push(x);
+            match self.nodes[x].down[0] {
+                NIL => break,
+                l => x = l,
+            }
+        }
+        self.splay(x);
+        x
+    }
+
+    /// Returns whether u and v belong to the same tree.
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        u == v || self.find_root(u) == self.find_root(v)
+    }
+
+    /// Adds an edge between u and v, after which u is a child of v; its tree
+    /// is re-rooted at u first. Returns false and does nothing if they are
+    /// already connected.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        self.evert(u);
+        self.nodes[u].up = v;
+        true
+    }
+
+    /// Removes the edge between u and v, leaving u at the root of its tree.
+    /// Returns false and does nothing if there is no such edge.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        if u == v {
+            return false;
+        }
+        self.evert(u);
+        self.access(v);
+        if self.nodes[v].down[0] != u || self.nodes[u].size != 1 {
+            return false;
+        }
+        self.nodes[v].down[0] = NIL;
+        self.nodes[u].up = NIL;
+        self.pull(v);
+        true
+    }
+
+    /// Returns the lowest common ancestor of u and v with respect to the
+    /// current root, or None if they aren't connected.
+    pub fn lca(&mut self, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        Some(self.access(v))
+    }
+
+    /// Returns the parent of u with respect to the current root, if any.
+    pub fn parent(&mut self, u: usize) -> Option<usize> {
+        self.access(u);
+        let mut x = self.nodes[u].down[0];
+        if x == NIL {
+            return None;
+        }
+        loop {
+            self.push(x);
+            match self.nodes[x].down[1] {
+                NIL => break,
+                r => x = r,
+            }
+        }
+        self.splay(x);
+        Some(x)
+    }
+
+    /// Gets the value of vertex u.
+    pub fn get(&mut self, u: usize) -> T::S {
+        self.splay(u);
+        self.nodes[u].val.clone()
+    }
+
+    /// Sets the value of vertex u.
+    pub fn set(&mut self, u: usize, val: T::S) {
+        self.access(u);
+        self.nodes[u].val = val;
+        self.pull(u);
+    }
+
+    // Makes the path from u to v the preferred path of a tree rooted at u,
+    // with v at the root of its splay tree.
+    fn expose_path(&mut self, u: usize, v: usize) {
+        assert!(self.connected(u, v), "Vertices {} and {} aren't connected.", u, v);
+        self.evert(u);
+        self.access(v);
+    }
+
+    /// Returns the aggregate of the values along the path from u to v,
+    /// in order. Note that this makes u the root of its tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if u and v aren't connected.
+    pub fn query(&mut self, u: usize, v: usize) -> T::S {
+        self.expose_path(u, v);
+        self.nodes[v].sum.clone()
+    }
+
+    /// Applies the endomorphism f to the values along the path from u to v.
+    /// Note that this makes u the root of its tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if u and v aren't connected.
+    pub fn update(&mut self, u: usize, v: usize, f: &T::F) {
+        self.expose_path(u, v);
+        self.apply(v, f);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::range_query::specs::{ArqAddSum, ArqMin, ArqPath};
+    use crate::rng::SmallRng;
+
+    // Finds the path from u to v in a forest given by its adjacency lists.
+    fn naive_path(adj: &[Vec<usize>], u: usize, v: usize) -> Option<Vec<usize>> {
+        let mut prev = vec![usize::MAX; adj.len()];
+        let mut stack = vec![u];
+        prev[u] = u;
+        while let Some(x) = stack.pop() {
+            for &y in &adj[x] {
+                if prev[y] == usize::MAX {
+                    prev[y] = x;
+                    stack.push(y);
+                }
+            }
+        }
+        if prev[v] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![v];
+        while *path.last().unwrap() != u {
+            path.push(prev[*path.last().unwrap()]);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    #[test]
+    fn test_link_cut() {
+        let mut lct = LinkCutTree::<ArqAddSum>::new(&[1, 2, 3, 4, 5]);
+        assert!(lct.link(0, 1));
+        assert!(lct.link(1, 2));
+        assert!(lct.link(3, 2));
+        assert!(!lct.link(0, 3));
+        assert_eq!(lct.query(0, 3), 10);
+        assert!(!lct.connected(0, 4));
+
+        lct.update(1, 3, &10);
+        assert_eq!(lct.query(0, 2), 26);
+        assert!(!lct.cut(0, 2));
+        assert!(lct.cut(2, 1));
+        assert!(!lct.connected(0, 3));
+        assert!(lct.link(4, 0));
+        assert_eq!(lct.query(4, 1), 18);
+
+        lct.evert(2);
+        assert_eq!(lct.find_root(3), 2);
+        assert_eq!(lct.parent(3), Some(2));
+        assert_eq!(lct.parent(2), None);
+        assert_eq!(lct.lca(1, 4), Some(4));
+        lct.evert(1);
+        assert_eq!(lct.lca(0, 4), Some(0));
+        assert_eq!(lct.lca(1, 3), None);
+    }
+
+    #[test]
+    fn test_link_cut_random() {
+        let n = 30;
+        let mut rng = SmallRng::new(13);
+        let mut sums = (0..n).map(|_| (rng.next_u64() % 100) as i64).collect::<Vec<_>>();
+        let mut mins = sums.clone();
+        let mut sum_lct = LinkCutTree::<ArqAddSum>::new(&sums);
+        let mut min_lct = LinkCutTree::<ArqMin>::new(&mins);
+        let ids = (0..n).map(|v| vec![v]).collect::<Vec<_>>();
+        let mut path_lct = LinkCutTree::<ArqPath>::new(&ids);
+        let mut adj = vec![vec![]; n];
+
+        for _ in 0..3000 {
+            let u = rng.next_u64() as usize % n;
+            let v = rng.next_u64() as usize % n;
+            let path = naive_path(&adj, u, v);
+            assert_eq!(sum_lct.connected(u, v), path.is_some());
+            match (rng.next_u64() % 4, path) {
+                (0, None) => {
+                    assert!(sum_lct.link(u, v));
+                    assert!(min_lct.link(v, u));
+                    assert!(path_lct.link(u, v));
+                    adj[u].push(v);
+                    adj[v].push(u);
+                }
+                (1, Some(path)) => {
+                    let is_edge = path.len() == 2;
+                    assert_eq!(sum_lct.cut(u, v), is_edge);
+                    assert_eq!(min_lct.cut(v, u), is_edge);
+                    assert_eq!(path_lct.cut(u, v), is_edge);
+                    if is_edge {
+                        adj[u].retain(|&w| w != v);
+                        adj[v].retain(|&w| w != u);
+                    }
+                }
+                (2, Some(path)) => {
+                    // ArqAddSum adds to the values whereas ArqMin assigns them.
+                    let x = (rng.next_u64() % 100) as i64 - 50;
+                    sum_lct.update(u, v, &x);
+                    min_lct.update(v, u, &x);
+                    for &w in &path {
+                        sums[w] += x;
+                        mins[w] = x;
+                    }
+                }
+                (_, Some(path)) => {
+                    let expected_sum = path.iter().map(|&w| sums[w]).sum::<i64>();
+                    let expected_min = path.iter().map(|&w| mins[w]).min().unwrap();
+                    assert_eq!(sum_lct.query(u, v), expected_sum);
+                    assert_eq!(min_lct.query(v, u), expected_min);
+                    assert_eq!(path_lct.query(u, v), path);
+                    let w = path[rng.next_u64() as usize % path.len()];
+                    assert_eq!(sum_lct.get(w), sums[w]);
+                    sums[w] -= 1;
+                    sum_lct.set(w, sums[w]);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Minimum cuts of undirected weighted graphs
+use super::dinic::Dinic;
+use super::{Graph, WeightedEdge};
+
+impl Graph<WeightedEdge> {
+    /// Gusfield's construction of a Gomory-Hu tree, treating every edge as
+    /// undirected with capacity equal to its weight: for all vertices u and
+    /// v, the minimum u-v cut in the graph equals the minimum weight on the
+    /// u-v path in the tree. Takes V - 1 maximum flow computations.
+    pub fn gomory_hu_tree(&self) -> Graph<WeightedEdge> {
+        let n = self.num_v();
+        let mut tree = Graph::new(n, n.saturating_sub(1));
+        if n < 2 {
+            return tree;
+        }
+        let mut network = Dinic::new(n, self.num_e());
+        for e in &self.edges {
+            network.add_edge_rcap(e.u, e.v, e.weight, e.weight);
+        }
+        let mut parent = vec![0; n];
+        for i in 1..n {
+            network.clear_flow();
+            let cut = network.dinic(i, parent[i]);
+            let side = network.source_side();
+            for j in i + 1..n {
+                if side[j] && parent[j] == parent[i] {
+                    parent[j] = i;
+                }
+            }
+            tree.add_weighted_undirected_edge(i, parent[i], cut);
+        }
+        tree
+    }
+
+    /// Stoer-Wagner algorithm for the global minimum cut, treating every
+    /// edge as undirected and its weight as non-negative: splits the
+    /// vertices into two non-empty sides so as to minimize the total weight
+    /// of edges across. Returns that weight and whether each vertex is on
+    /// one of the sides. Takes O(V^3) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has fewer than two vertices.
+    pub fn global_min_cut(&self) -> (i64, Vec<bool>) {
+        let n = self.num_v();
+        assert!(n >= 2, "a cut needs at least two vertices");
+        let mut w = vec![vec![0; n]; n];
+        for e in &self.edges {
+            if e.u != e.v {
+                w[e.u][e.v] += e.weight;
+                w[e.v][e.u] += e.weight;
+            }
+        }
+        // Each remaining vertex stands for the group of vertices merged into it.
+        let mut groups = (0..n).map(|v| vec![v]).collect::<Vec<_>>();
+        let mut active = (0..n).collect::<Vec<_>>();
+        let mut best = (i64::MAX, vec![]);
+        while active.len() > 1 {
+            // Adds vertices in order of their total weight to those already
+            // added: the last one's weight is a minimum cut between it and
+            // the one before, which are then merged.
+            let mut added = vec![false; n];
+            let mut weight = vec![0; n];
+            let mut prev = active[0];
+            for step in 0..active.len() {
+                let v = *active.iter().filter(|&&v| !added[v]).max_by_key(|&&v| weight[v]).unwrap();
+                if step + 1 < active.len() {
+                    added[v] = true;
+                    for &x in &active {
+                        weight[x] += w[v][x];
+                    }
+                    prev = v;
+                    continue;
+                }
+                if weight[v] < best.0 {
+                    best = (weight[v], groups[v].clone());
+                }
+                let group = std::mem::take(&mut groups[v]);
+                groups[prev].extend(group);
+                for &x in &active {
+                    w[prev][x] += w[v][x];
+                    w[x][prev] = w[prev][x];
+                }
+                active.retain(|&x| x != v);
+            }
+        }
+        let mut side = vec![false; n];
+        for v in best.1 {
+            side[v] = true;
+        }
+        (best.0, side)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    fn cut_weight(graph: &Graph<WeightedEdge>, side: &[bool]) -> i64 {
+        graph.edges.iter().filter(|e| side[e.u] != side[e.v]).map(|e| e.weight).sum()
+    }
+
+    #[test]
+    fn test_min_cuts() {
+        // two triangles joined by a light edge 2-3 and a heavier one 0-5
+        let mut graph = Graph::new(6, 8);
+        for &(u, v, w) in &[(0, 1, 5), (1, 2, 5), (2, 0, 5), (3, 4, 5), (4, 5, 5), (5, 3, 5), (2, 3, 1), (0, 5, 2)] {
+            graph.add_weighted_undirected_edge(u, v, w);
+        }
+        let (cut, side) = graph.global_min_cut();
+        assert_eq!(cut, 3);
+        assert_eq!(side[0..3], [side[0]; 3]);
+        assert_eq!(side[3..6], [!side[0]; 3]);
+
+        let tree = graph.gomory_hu_tree();
+        assert_eq!(tree.num_e(), 5);
+        let mut weights = (0..5).map(|e| tree.edge(e).weight).collect::<Vec<_>>();
+        weights.sort_unstable();
+        assert_eq!(weights, vec![3, 10, 10, 11, 11]);
+    }
+
+    #[test]
+    fn test_min_cuts_random() {
+        let mut rng = SmallRng::new(28);
+        for _ in 0..50 {
+            let n = 2 + rng.next_u64() as usize % 7;
+            let m = rng.next_u64() as usize % 16;
+            let mut graph = Graph::new(n, m);
+            for _ in 0..m {
+                let (u, v) = (rng.next_u64() as usize % n, rng.next_u64() as usize % n);
+                graph.add_weighted_undirected_edge(u, v, (rng.next_u64() % 10) as i64);
+            }
+
+            // brute_force[mask] is the weight of the cut with side mask
+            let brute_force = (0..1usize << n)
+                .map(|mask| cut_weight(&graph, &(0..n).map(|v| mask >> v & 1 == 1).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            let (cut, side) = graph.global_min_cut();
+            assert_eq!(cut, *brute_force[1..(1 << n) - 1].iter().min().unwrap());
+            assert_eq!(cut_weight(&graph, &side), cut);
+            assert!(side.iter().any(|&b| b) && side.iter().any(|&b| !b));
+
+            // the tree's path minima are the pairwise minimum cuts
+            let tree = graph.gomory_hu_tree();
+            for s in 0..n {
+                let mut path_min = vec![None; n];
+                path_min[s] = Some(i64::MAX);
+                let mut stack = vec![s];
+                while let Some(u) = stack.pop() {
+                    for a in tree.adj_list(u) {
+                        if path_min[a.v].is_none() {
+                            path_min[a.v] = path_min[u].map(|m| m.min(tree.edge(a.edge_id).weight));
+                            stack.push(a.v);
+                        }
+                    }
+                }
+                for t in 0..n {
+                    if s != t {
+                        let expected = (0..1usize << n)
+                            .filter(|&mask| mask >> s & 1 == 1 && mask >> t & 1 == 0)
+                            .map(|mask| brute_force[mask])
+                            .min();
+                        assert_eq!(path_min[t], expected);
+                    }
+                }
+            }
+        }
+    }
+}
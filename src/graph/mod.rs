@@ -12,6 +12,16 @@ pub mod util;
 pub mod rerooting;
 pub mod lca;
 pub mod dinic;
+pub mod link_cut;
+pub mod hld;
+pub mod centroid;
+pub mod dominator;
+pub mod two_sat;
+pub mod shortest_path;
+pub mod bipartite;
+pub mod blossom;
+pub mod hungarian;
+pub mod min_cut;
 
 use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::cmp::Reverse;
@@ -0,0 +1,357 @@
+//! Shortest paths with negative or 0-1 weights, and between all pairs
+use super::{Graph, WeightedEdge};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+impl Graph<WeightedEdge> {
+    // Lists each edge as (u, v, weight), twice for an undirected edge.
+    fn arcs(&self) -> Vec<(usize, usize, i64)> {
+        self.adj
+            .iter()
+            .flat_map(|(&u, adj)| adj.iter().map(move |a| (u, a.v, self.edges[a.edge_id].weight)))
+            .collect()
+    }
+
+    /// Bellman-Ford single-source shortest paths on a graph with arbitrary
+    /// weights, in O(VE) time. Returns the distance to each vertex, or None
+    /// if it's unreachable. If some distances are unbounded because of a
+    /// negative cycle reachable from src, returns instead the sorted list of
+    /// all such vertices: those reachable from a negative cycle.
+    pub fn bellman_ford(&self, src: usize) -> Result<Vec<Option<i64>>, Vec<usize>> {
+        let n = self.num_v();
+        let arcs = self.arcs();
+        let mut dist = vec![None; n];
+        dist[src] = Some(0);
+        let relax = |dist: &mut Vec<Option<i64>>| {
+            let mut changed = vec![];
+            for &(u, v, w) in &arcs {
+                if let Some(du) = dist[u] {
+                    if dist[v].map_or(true, |dv| du + w < dv) {
+                        dist[v] = Some(du + w);
+                        changed.push(v);
+                    }
+                }
+            }
+            changed
+        };
+        for _ in 1..n {
+            if relax(&mut dist).is_empty() {
+                return Ok(dist);
+            }
+        }
+
+        // Vertices that can still be improved are reachable from a negative
+        // cycle, and so is everything reachable from them.
+        let mut stack = relax(&mut dist);
+        if stack.is_empty() {
+            return Ok(dist);
+        }
+        let mut affected = vec![false; n];
+        for &v in &stack {
+            affected[v] = true;
+        }
+        while let Some(u) = stack.pop() {
+            for a in self.adj.get(&u).into_iter().flatten() {
+                if !affected[a.v] {
+                    affected[a.v] = true;
+                    stack.push(a.v);
+                }
+            }
+        }
+        Err((0..n).filter(|&v| affected[v]).collect())
+    }
+
+    /// Shortest Path Faster Algorithm: Bellman-Ford with a queue of the
+    /// vertices whose distance changed. Same results as bellman_ford(), and
+    /// usually much faster, but O(VE) in the worst case.
+    pub fn spfa(&self, src: usize) -> Result<Vec<Option<i64>>, Vec<usize>> {
+        let n = self.num_v();
+        let mut dist = vec![None; n];
+        let mut num_edges = vec![0; n];
+        let mut in_queue = vec![false; n];
+        let mut queue = VecDeque::from(vec![src]);
+        dist[src] = Some(0);
+        in_queue[src] = true;
+        while let Some(u) = queue.pop_front() {
+            in_queue[u] = false;
+            let du = dist[u].unwrap();
+            for a in self.adj.get(&u).into_iter().flatten() {
+                let dv = du + self.edges[a.edge_id].weight;
+                if dist[a.v].map_or(true, |old| dv < old) {
+                    dist[a.v] = Some(dv);
+                    // a shortest path with n edges must contain a cycle
+                    num_edges[a.v] = num_edges[u] + 1;
+                    if num_edges[a.v] >= n {
+                        return self.bellman_ford(src);
+                    }
+                    if !in_queue[a.v] {
+                        in_queue[a.v] = true;
+                        queue.push_back(a.v);
+                    }
+                }
+            }
+        }
+        Ok(dist)
+    }
+
+    /// Single-source shortest paths on a graph whose weights are all 0 or 1,
+    /// in O(V + E) time. Returns None for unreachable vertices.
+    pub fn zero_one_bfs(&self, src: usize) -> Vec<Option<i64>> {
+        let mut dist = vec![None; self.num_v()];
+        let mut deque = VecDeque::from(vec![(0, src)]);
+        dist[src] = Some(0);
+        while let Some((du, u)) = deque.pop_front() {
+            if dist[u] < Some(du) {
+                continue;
+            }
+            for a in self.adj.get(&u).into_iter().flatten() {
+                let w = self.edges[a.edge_id].weight;
+                assert!(w == 0 || w == 1, "0-1 BFS on an edge of weight {}", w);
+                if dist[a.v].map_or(true, |dv| du + w < dv) {
+                    dist[a.v] = Some(du + w);
+                    if w == 0 {
+                        deque.push_front((du, a.v));
+                    } else {
+                        deque.push_back((du + 1, a.v));
+                    }
+                }
+            }
+        }
+        dist
+    }
+
+    /// Floyd-Warshall all-pairs shortest paths in O(V^3) time: dist[u][v] is
+    /// the distance from u to v, or None if v is unreachable from u.
+    /// Returns None if the graph contains a negative cycle.
+    pub fn floyd_warshall(&self) -> Option<Vec<Vec<Option<i64>>>> {
+        let n = self.num_v();
+        let mut dist = vec![vec![None; n]; n];
+        for (u, row) in dist.iter_mut().enumerate() {
+            row[u] = Some(0);
+        }
+        for (u, v, w) in self.arcs() {
+            if dist[u][v].map_or(true, |d| w < d) {
+                dist[u][v] = Some(w);
+            }
+        }
+        for k in 0..n {
+            let row_k = dist[k].clone();
+            for row in &mut dist {
+                let dik = match row[k] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                for (dij, &dkj) in row.iter_mut().zip(&row_k) {
+                    if let Some(dkj) = dkj {
+                        if dij.map_or(true, |d| dik + dkj < d) {
+                            *dij = Some(dik + dkj);
+                        }
+                    }
+                }
+            }
+            // the first negative cycle found goes through k; stopping here
+            // keeps the distances from decreasing until they overflow
+            if dist[k][k] < Some(0) {
+                return None;
+            }
+        }
+        Some(dist)
+    }
+
+    /// Johnson's all-pairs shortest paths in O(VE log V) time, faster than
+    /// floyd_warshall() on sparse graphs: Bellman-Ford potentials make all
+    /// weights non-negative, then Dijkstra runs from each vertex.
+    /// Same results as floyd_warshall().
+    pub fn johnson(&self) -> Option<Vec<Vec<Option<i64>>>> {
+        let n = self.num_v();
+        let arcs = self.arcs();
+        // distances from a virtual source with a 0-weight edge to each vertex
+        let mut h = vec![0; n];
+        let mut changed = true;
+        for _ in 0..=n {
+            changed = false;
+            for &(u, v, w) in &arcs {
+                if h[u] + w < h[v] {
+                    h[v] = h[u] + w;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        if changed {
+            return None;
+        }
+
+        let dist = (0..n)
+            .map(|src| {
+                let mut dist = vec![None; n];
+                let mut heap = BinaryHeap::new();
+                dist[src] = Some(0);
+                heap.push((Reverse(0), src));
+                while let Some((Reverse(du), u)) = heap.pop() {
+                    if dist[u] < Some(du) {
+                        continue;
+                    }
+                    for a in self.adj.get(&u).into_iter().flatten() {
+                        let dv = du + self.edges[a.edge_id].weight + h[u] - h[a.v];
+                        if dist[a.v].map_or(true, |old| dv < old) {
+                            dist[a.v] = Some(dv);
+                            heap.push((Reverse(dv), a.v));
+                        }
+                    }
+                }
+                (0..n).map(|v| dist[v].map(|d| d - h[src] + h[v])).collect()
+            })
+            .collect();
+        Some(dist)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_negative_weights() {
+        let mut graph = Graph::new(5, 6);
+        graph.add_weighted_edge(0, 1, 4);
+        graph.add_weighted_edge(0, 2, 5);
+        graph.add_weighted_edge(2, 1, -3);
+        graph.add_weighted_edge(1, 3, 2);
+        graph.add_weighted_edge(4, 3, -7);
+        let expected = vec![Some(0), Some(2), Some(5), Some(4), None];
+        assert_eq!(graph.bellman_ford(0), Ok(expected.clone()));
+        assert_eq!(graph.spfa(0), Ok(expected.clone()));
+        let all_pairs = graph.floyd_warshall().unwrap();
+        assert_eq!(all_pairs[0], expected);
+        assert_eq!(all_pairs[4], vec![None, None, None, Some(-7), Some(0)]);
+        assert_eq!(graph.johnson(), Some(all_pairs));
+
+        // a negative cycle 1 -> 3 -> 1, unreachable from 4
+        graph.add_weighted_edge(3, 1, -3);
+        assert_eq!(graph.bellman_ford(0), Err(vec![1, 3]));
+        assert_eq!(graph.spfa(0), Err(vec![1, 3]));
+        assert_eq!(graph.bellman_ford(4), Err(vec![1, 3]));
+        assert_eq!(graph.bellman_ford(2), Err(vec![1, 3]));
+        assert_eq!(graph.floyd_warshall(), None);
+        assert_eq!(graph.johnson(), None);
+    }
+
+    #[test]
+    fn test_negative_cycle_large() {
+        // every pair of vertices forms a negative cycle
+        let n = 80;
+        let mut graph = Graph::new(n, n * (n - 1));
+        for u in 0..n {
+            for v in 0..n {
+                if u != v {
+                    graph.add_weighted_edge(u, v, -1);
+                }
+            }
+        }
+        assert_eq!(graph.floyd_warshall(), None);
+        assert_eq!(graph.johnson(), None);
+        assert_eq!(graph.bellman_ford(0), Err((0..n).collect()));
+        assert_eq!(graph.spfa(0), Err((0..n).collect()));
+    }
+
+    #[test]
+    fn test_zero_one_bfs() {
+        let mut graph = Graph::new(4, 4);
+        graph.add_weighted_edge(0, 1, 1);
+        graph.add_weighted_edge(1, 2, 1);
+        graph.add_weighted_edge(0, 2, 1);
+        graph.add_weighted_undirected_edge(2, 3, 0);
+        assert_eq!(graph.zero_one_bfs(0), vec![Some(0), Some(1), Some(1), Some(1)]);
+        assert_eq!(graph.zero_one_bfs(3), vec![None, None, Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn test_shortest_paths_random() {
+        let mut rng = SmallRng::new(22);
+        for &(n, m) in &[(1, 0), (2, 3), (5, 10), (10, 15), (20, 60)] {
+            for _ in 0..20 {
+                let mut graph = Graph::new(n, m);
+                let mut zero_one = Graph::new(n, m);
+                for _ in 0..m {
+                    let u = rng.next_u64() as usize % n;
+                    let v = rng.next_u64() as usize % n;
+                    graph.add_weighted_edge(u, v, (rng.next_u64() % 20) as i64 - 3);
+                    zero_one.add_weighted_edge(u, v, (rng.next_u64() % 2) as i64);
+                }
+                let all_pairs = graph.floyd_warshall();
+                assert_eq!(graph.johnson(), all_pairs);
+                for src in 0..n {
+                    let bf = graph.bellman_ford(src);
+                    assert_eq!(graph.spfa(src), bf);
+                    let unbounded = brute_unbounded(&graph, src);
+                    match bf {
+                        Ok(dist) => {
+                            assert!(unbounded.is_empty());
+                            if let Some(all_pairs) = &all_pairs {
+                                assert_eq!(dist, all_pairs[src]);
+                            }
+                        }
+                        Err(affected) => {
+                            assert_eq!(affected, unbounded);
+                            assert!(all_pairs.is_none());
+                        }
+                    }
+
+                    let (dist, _) = zero_one.dijkstra(src);
+                    let expected = dist.iter().map(|&d| if d == usize::MAX { None } else { Some(d as i64) }).collect::<Vec<_>>();
+                    assert_eq!(zero_one.zero_one_bfs(src), expected);
+                }
+            }
+        }
+    }
+
+    // Lists the vertices reachable from src through a vertex c with a closed
+    // walk of negative weight: every negative cycle has at most n edges.
+    fn brute_unbounded(graph: &Graph<WeightedEdge>, src: usize) -> Vec<usize> {
+        let n = graph.num_v();
+        let arcs = graph.arcs();
+        let reach = |s: usize| {
+            let mut seen = vec![false; n];
+            seen[s] = true;
+            let mut stack = vec![s];
+            while let Some(u) = stack.pop() {
+                for &(a, b, _) in &arcs {
+                    if a == u && !seen[b] {
+                        seen[b] = true;
+                        stack.push(b);
+                    }
+                }
+            }
+            seen
+        };
+        let mut affected = vec![false; n];
+        for (c, _) in reach(src).into_iter().enumerate().filter(|&(_, r)| r) {
+            // walk[x] is the min weight of a walk from c to x with k edges
+            let mut walk = vec![None; n];
+            walk[c] = Some(0i64);
+            let mut negative = false;
+            for _ in 0..n {
+                let mut next = vec![None; n];
+                for &(a, b, w) in &arcs {
+                    if let Some(wa) = walk[a] {
+                        if next[b].map_or(true, |wb| wa + w < wb) {
+                            next[b] = Some(wa + w);
+                        }
+                    }
+                }
+                walk = next;
+                negative |= walk[c].map_or(false, |w| w < 0);
+            }
+            if negative {
+                for (v, r) in reach(c).into_iter().enumerate() {
+                    affected[v] |= r;
+                }
+            }
+        }
+        (0..n).filter(|&v| affected[v]).collect()
+    }
+}
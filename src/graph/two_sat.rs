@@ -0,0 +1,190 @@
+//! 2-SAT solver over boolean variables
+use super::connectivity::ConnectivityGraph;
+use std::ops::Not;
+
+/// A literal: a variable or its negation. Negate it with the ! operator.
+#[derive(Debug,Copy,Clone,PartialEq,Eq,Hash)]
+pub struct Lit(usize); // 2 * var, plus 1 if negated
+
+impl Lit {
+    /// The literal "variable i is true".
+    pub fn x(i: usize) -> Self {
+        Self(2 * i)
+    }
+
+    /// The literal "variable i is false".
+    pub fn not_x(i: usize) -> Self {
+        Self(2 * i + 1)
+    }
+
+    /// Returns the variable of this literal.
+    pub fn var(self) -> usize {
+        self.0 / 2
+    }
+
+    /// Returns whether this literal is a negation.
+    pub fn is_neg(self) -> bool {
+        self.0 & 1 == 1
+    }
+}
+
+impl Not for Lit {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(self.0 ^ 1)
+    }
+}
+
+/// A conjunction of clauses on two literals each, whose satisfiability is
+/// decided in linear time by the strongly connected components of its
+/// implication graph.
+#[derive(Debug,Default,Clone)]
+pub struct TwoSat {
+    num_vars: usize,
+    clauses: Vec<(Lit, Lit)>,
+}
+
+impl TwoSat {
+    /// Initializes a formula on variables 0..num_vars and no clauses.
+    pub fn new(num_vars: usize) -> Self {
+        Self { num_vars, clauses: vec![] }
+    }
+
+    /// Returns the number of variables, including auxiliary ones.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Adds a fresh variable and returns its index.
+    pub fn new_var(&mut self) -> usize {
+        self.num_vars += 1;
+        self.num_vars - 1
+    }
+
+    /// Requires a || b.
+    pub fn add_clause(&mut self, a: Lit, b: Lit) {
+        assert!(a.var() < self.num_vars && b.var() < self.num_vars);
+        self.clauses.push((a, b));
+    }
+
+    /// Requires a to hold.
+    pub fn add_unit(&mut self, a: Lit) {
+        self.add_clause(a, a);
+    }
+
+    /// Requires a -> b, i.e., !a || b.
+    pub fn add_implication(&mut self, a: Lit, b: Lit) {
+        self.add_clause(!a, b);
+    }
+
+    /// Requires exactly one of a and b to hold.
+    pub fn add_xor(&mut self, a: Lit, b: Lit) {
+        self.add_clause(a, b);
+        self.add_clause(!a, !b);
+    }
+
+    /// Requires a and b to be equal.
+    pub fn add_equiv(&mut self, a: Lit, b: Lit) {
+        self.add_implication(a, b);
+        self.add_implication(b, a);
+    }
+
+    /// Requires at most one of the literals to hold, with O(len) clauses:
+    /// the i-th auxiliary variable is implied by any of the first i + 2
+    /// literals, and forbids the ones after them. This adds len - 2
+    /// auxiliary variables, if len > 2.
+    pub fn at_most_one(&mut self, lits: &[Lit]) {
+        let mut prefix = match lits.first() {
+            Some(&a) => a,
+            None => return,
+        };
+        for (i, &a) in lits.iter().enumerate().skip(1) {
+            self.add_implication(prefix, !a);
+            if i + 1 < lits.len() {
+                let p = Lit::x(self.new_var());
+                self.add_implication(prefix, p);
+                self.add_implication(a, p);
+                prefix = p;
+            }
+        }
+    }
+
+    /// Finds a satisfying assignment of all variables, including auxiliary
+    /// ones, or returns None if there is none.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut graph = ConnectivityGraph::new(2 * self.num_vars);
+        for &(a, b) in &self.clauses {
+            graph.add_two_sat_clause(a.0, b.0);
+        }
+        graph.build(true);
+        graph.two_sat_assign()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_two_sat() {
+        let mut sat = TwoSat::new(3);
+        let (x, y, z) = (Lit::x(0), Lit::x(1), Lit::x(2));
+        sat.add_clause(x, z);
+        sat.add_clause(!y, !z);
+        sat.add_unit(y);
+        assert_eq!(sat.solve(), Some(vec![true, true, false]));
+
+        sat.add_xor(x, z);
+        sat.add_equiv(Lit::not_x(0), z);
+        assert_eq!(sat.solve(), Some(vec![true, true, false]));
+        sat.add_implication(y, !x);
+        assert_eq!(sat.solve(), None);
+    }
+
+    #[test]
+    fn test_at_most_one() {
+        let mut sat = TwoSat::new(5);
+        let lits = (0..5).map(Lit::x).collect::<Vec<_>>();
+        sat.at_most_one(&lits);
+        assert_eq!(sat.num_vars(), 8);
+        sat.add_unit(lits[3]);
+        let vals = sat.solve().unwrap();
+        assert_eq!(&vals[..5], &[false, false, false, true, false]);
+
+        sat.add_clause(lits[0], lits[4]);
+        assert_eq!(sat.solve(), None);
+    }
+
+    #[test]
+    fn test_two_sat_random() {
+        let mut rng = SmallRng::new(21);
+        for _ in 0..300 {
+            let n = 1 + rng.next_u64() as usize % 8;
+            let mut sat = TwoSat::new(n);
+            let lit = |r: u64| {
+                let i = (r >> 1) as usize % n;
+                if r & 1 == 0 { Lit::x(i) } else { Lit::not_x(i) }
+            };
+            let mut clauses = vec![];
+            for _ in 0..rng.next_u64() % 10 {
+                let (a, b) = (lit(rng.next_u64()), lit(rng.next_u64()));
+                sat.add_clause(a, b);
+                clauses.push((a, b));
+            }
+            let group = (0..n).filter(|_| rng.next_u64() % 2 == 0).map(|i| lit(2 * i as u64)).collect::<Vec<_>>();
+            sat.at_most_one(&group);
+
+            let holds = |vals: &[bool], a: Lit| vals[a.var()] != a.is_neg();
+            let valid = |vals: &[bool]| {
+                clauses.iter().all(|&(a, b)| holds(vals, a) || holds(vals, b))
+                    && group.iter().filter(|&&a| holds(vals, a)).count() <= 1
+            };
+            let satisfiable = (0..1 << n).any(|mask: usize| valid(&(0..n).map(|i| mask >> i & 1 == 1).collect::<Vec<_>>()));
+            match sat.solve() {
+                Some(vals) => assert!(valid(&vals[..n])),
+                None => assert!(!satisfiable),
+            }
+        }
+    }
+}
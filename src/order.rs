@@ -73,6 +73,21 @@ impl SparseIndex {
     pub fn compress(&self, q: i64) -> Result<usize, usize> {
         self.coords.binary_search(&q)
     }
+
+    /// Returns the i-th smallest coordinate, i.e., the inverse of compress()
+    pub fn decompress(&self, i: usize) -> i64 {
+        self.coords[i]
+    }
+
+    /// Returns the number of distinct coordinates
+    pub fn len(&self) -> usize {
+        self.coords.len()
+    }
+
+    /// Returns true if there are no coordinates
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
 }
 
 /// Represents a maximum (upper envelope) of a collection of linear functions of one
@@ -81,6 +96,7 @@ impl SparseIndex {
 /// asymptotically suboptimal but simple algorithm with good amortized performance:
 /// N inserts interleaved with Q queries yields O(N sqrt Q + Q log N) time complexity
 /// in general, or O((N + Q) log N) if all queries come after all inserts.
+/// For exact integer arithmetic, see range_query::LiChaoTree instead.
 // Proof: the Q log N term comes from calls to slice_lower_bound(). As for the N sqrt Q,
 //        note that between successive times when the hull is rebuilt, O(N) work is done,
 //        and the running totals of insertions and queries satisfy del_N (del_Q + 1) > N.
@@ -0,0 +1,198 @@
+//! Segment Tree Beats, an Associative Range Query Tree whose updates may fail
+
+use super::BeatsSpec;
+
+/// A variant of StaticArq for updates that aren't endomorphisms on the
+/// aggregates, such as range chmin/chmax combined with range sum queries:
+///
+/// - update(l, r, f) replaces a_i (l <= i <= r) by f(a_i)
+/// - query(l, r) returns the aggregate a_l + a_{l+1} + ... + a_r
+///
+/// Whenever BeatsSpec::apply() fails on a node covered by an update, the
+/// update is broken down to the node's children, down to the leaves if need
+/// be. The running time thus depends on the spec; see ArqChminChmaxSum.
+///
+/// Unlike StaticArq, the tree is stored top-down with its size rounded up to
+/// a power of two, so that every node covers a contiguous range of entries.
+pub struct BeatsArq<T: BeatsSpec> {
+    val: Vec<T::S>,
+    app: Vec<Option<T::F>>,
+    len: usize,
+}
+
+impl<T: BeatsSpec> BeatsArq<T> {
+    /// Initializes a balanced binary tree on top of the given sequence.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let len = init_val.len();
+        let size = len.next_power_of_two();
+        let mut val = vec![T::identity(); size];
+        val.extend_from_slice(init_val);
+        val.resize(2 * size, T::identity());
+        let app = vec![None; size];
+
+        let mut arq = Self { val, app, len };
+        for p in (1..size).rev() {
+            arq.pull(p);
+        }
+        arq
+    }
+
+    fn apply(&mut self, p: usize, f: &T::F, s: usize) {
+        match T::apply(f, &self.val[p], s as i64) {
+            Some(val) => {
+                self.val[p] = val;
+                if let Some(lazy) = self.app.get_mut(p) {
+                    let h = match *lazy {
+                        Some(ref g) => T::compose(f, g),
+                        None => f.clone(),
+                    };
+                    *lazy = Some(h);
+                }
+            }
+            None => {
+                assert!(p < self.app.len(), "BeatsSpec::apply() failed on a leaf");
+                self.push(p, s);
+                self.apply(p << 1, f, s / 2);
+                self.apply(p << 1 | 1, f, s / 2);
+                self.pull(p);
+            }
+        }
+    }
+
+    fn push(&mut self, p: usize, s: usize) {
+        if let Some(ref f) = self.app[p].take() {
+            self.apply(p << 1, f, s / 2);
+            self.apply(p << 1 | 1, f, s / 2);
+        }
+    }
+
+    fn pull(&mut self, p: usize) {
+        self.val[p] = T::op(&self.val[p << 1], &self.val[p << 1 | 1]);
+    }
+
+    /// Applies the update f to all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn update(&mut self, l: usize, r: usize, f: &T::F) {
+        assert!(l > r || r < self.len, "Index out of bound. length:{}, but r:{}.", self.len, r);
+        self.update_rec(1, 0, self.app.len(), l, r, f);
+    }
+
+    fn update_rec(&mut self, p: usize, lo: usize, hi: usize, l: usize, r: usize, f: &T::F) {
+        if r < lo || hi <= l {
+            return;
+        }
+        if l <= lo && hi - 1 <= r {
+            self.apply(p, f, hi - lo);
+            return;
+        }
+        self.push(p, hi - lo);
+        let mid = (lo + hi) / 2;
+        self.update_rec(p << 1, lo, mid, l, r, f);
+        self.update_rec(p << 1 | 1, mid, hi, l, r, f);
+        self.pull(p);
+    }
+
+    /// Returns the aggregate range query on all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn query(&mut self, l: usize, r: usize) -> T::S {
+        assert!(l > r || r < self.len, "Index out of bound. length:{}, but r:{}.", self.len, r);
+        self.query_rec(1, 0, self.app.len(), l, r)
+    }
+
+    fn query_rec(&mut self, p: usize, lo: usize, hi: usize, l: usize, r: usize) -> T::S {
+        if r < lo || hi <= l {
+            return T::identity();
+        }
+        if l <= lo && hi - 1 <= r {
+            return self.val[p].clone();
+        }
+        self.push(p, hi - lo);
+        let mid = (lo + hi) / 2;
+        let l_agg = self.query_rec(p << 1, lo, mid, l, r);
+        let r_agg = self.query_rec(p << 1 | 1, mid, hi, l, r);
+        T::op(&l_agg, &r_agg)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::range_query::specs::ArqChminChmaxSum;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_chmin_chmax_sum() {
+        let init = [5, 1, 4, 2, 3];
+        let leaves = init.iter().map(|&v| ArqChminChmaxSum::leaf(v)).collect::<Vec<_>>();
+        let mut arq = BeatsArq::<ArqChminChmaxSum>::new(&leaves);
+
+        assert_eq!(arq.query(0, 4).sum, 15);
+        arq.update(0, 4, &ArqChminChmaxSum::chmin(3));
+        assert_eq!(arq.query(0, 4).sum, 12);
+        arq.update(1, 3, &ArqChminChmaxSum::chmax(3));
+        assert_eq!(arq.query(0, 4).sum, 15);
+        arq.update(2, 4, &ArqChminChmaxSum::add(-4));
+        let agg = arq.query(0, 4);
+        assert_eq!((agg.sum, agg.min, agg.max), (3, -1, 3));
+        assert_eq!(arq.query(3, 1), ArqChminChmaxSum::identity());
+    }
+
+    #[test]
+    fn test_chmin_chmax_extremes() {
+        let leaves = [-3, 0, -7, -1].iter().map(|&v| ArqChminChmaxSum::leaf(v)).collect::<Vec<_>>();
+        let mut arq = BeatsArq::<ArqChminChmaxSum>::new(&leaves);
+        arq.update(1, 1, &ArqChminChmaxSum::chmax(i64::MAX));
+        assert_eq!(arq.query(1, 1), ArqChminChmaxSum::leaf(i64::MAX));
+        assert_eq!(arq.query(0, 3).sum, i64::MAX - 11);
+        arq.update(0, 3, &ArqChminChmaxSum::chmin(-2));
+        assert_eq!(arq.query(0, 3).sum, -14);
+
+        let leaves = [2, 0, 5].iter().map(|&v| ArqChminChmaxSum::leaf(v)).collect::<Vec<_>>();
+        let mut arq = BeatsArq::<ArqChminChmaxSum>::new(&leaves);
+        arq.update(2, 2, &ArqChminChmaxSum::chmin(i64::MIN));
+        assert_eq!(arq.query(2, 2), ArqChminChmaxSum::leaf(i64::MIN));
+        assert_eq!(arq.query(0, 2).sum, i64::MIN + 2);
+        arq.update(0, 2, &ArqChminChmaxSum::chmax(1));
+        assert_eq!(arq.query(0, 2).sum, 4);
+    }
+
+    #[test]
+    fn test_chmin_chmax_sum_random() {
+        let n = 37;
+        let mut rng = SmallRng::new(42);
+        let mut naive = (0..n).map(|_| (rng.next_u64() % 100) as i64).collect::<Vec<_>>();
+        let leaves = naive.iter().map(|&v| ArqChminChmaxSum::leaf(v)).collect::<Vec<_>>();
+        let mut arq = BeatsArq::<ArqChminChmaxSum>::new(&leaves);
+
+        for _ in 0..2000 {
+            let a = rng.next_u64() as usize % n;
+            let b = rng.next_u64() as usize % n;
+            let (l, r) = (a.min(b), a.max(b));
+            let x = (rng.next_u64() % 100) as i64 - 50;
+            let f = match rng.next_u64() % 4 {
+                0 => ArqChminChmaxSum::chmin(x),
+                1 => ArqChminChmaxSum::chmax(x),
+                2 => ArqChminChmaxSum::add(x),
+                _ => {
+                    let agg = arq.query(l, r);
+                    let range = &naive[l..=r];
+                    assert_eq!(agg.sum, range.iter().sum::<i64>());
+                    assert_eq!(agg.min, *range.iter().min().unwrap());
+                    assert_eq!(agg.max, *range.iter().max().unwrap());
+                    continue;
+                }
+            };
+            arq.update(l, r, &f);
+            let (add, lo, hi) = f;
+            for v in &mut naive[l..=r] {
+                *v = (*v + add).max(lo).min(hi);
+            }
+        }
+    }
+}
@@ -102,12 +102,17 @@ impl<T: ArqSpec> DynamicArq<T> {
             self.nodes.push(DynamicArqNode::default());
             self.nodes[p].down = (self.nodes.len() - 2, self.nodes.len() - 1)
         };
-        let (lp, rp) = self.nodes[p].down;
         let ls = s / 2;
         if let Some(ref f) = self.nodes[p].app.take() {
+            // In a persistent tree, the children may be shared with other
+            // versions, so the update is pushed to copies of them instead.
+            let (lp, rp) = self.nodes[p].down;
+            let (lp, rp) = (self.clone_node(lp), self.clone_node(rp));
+            self.nodes[p].down = (lp, rp);
             self.nodes[lp].apply(f, ls);
             self.nodes[rp].apply(f, s - ls);
         }
+        let (lp, rp) = self.nodes[p].down;
         ((lp, ls), (rp, s - ls))
     }
 
@@ -128,6 +133,37 @@ impl<T: ArqSpec> DynamicArq<T> {
         }
     }
 
+    /// Returns the number of nodes allocated so far.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Discards all nodes that are unreachable from the given views, which
+    /// are returned relocated in the same order. Other views are invalidated.
+    pub fn retain(&mut self, views: &[ArqView]) -> Vec<ArqView> {
+        let none = usize::MAX;
+        let mut new_id = vec![none; self.nodes.len()];
+        let mut nodes = vec![];
+        let mut stack = views.iter().map(|&(p, _)| p).collect::<Vec<_>>();
+        while let Some(p) = stack.pop() {
+            if p == none || new_id[p] != none {
+                continue;
+            }
+            new_id[p] = nodes.len();
+            nodes.push(std::mem::take(&mut self.nodes[p]));
+            let (lp, rp) = nodes[new_id[p]].down;
+            stack.push(lp);
+            stack.push(rp);
+        }
+        for node in &mut nodes {
+            if node.down.0 != none {
+                node.down = (new_id[node.down.0], new_id[node.down.1]);
+            }
+        }
+        self.nodes = nodes;
+        views.iter().map(|&(p, s)| (new_id[p], s)).collect()
+    }
+
     /// Applies the endomorphism f to all entries from l to r, inclusive.
     /// If l == r, the updates are eager. Otherwise, they are lazy.
     pub fn update(&mut self, view: ArqView, l: i64, r: i64, f: &T::F) -> ArqView {
@@ -165,6 +201,76 @@ impl<T: ArqSpec> DynamicArq<T> {
             T::op(&l_agg, &r_agg)
         }
     }
+
+    /// Binary searches for the largest r such that pred(a_l + ... + a_{r-1})
+    /// holds, where pred(identity) must hold. If pred is monotone, i.e., once
+    /// false it remains false as r grows, r is simply the first position at
+    /// which pred fails, or size if it never does. The order of op is
+    /// preserved, so non-commutative monoids are fine.
+    pub fn max_right(&mut self, view: ArqView, l: i64, pred: impl Fn(&T::S) -> bool) -> i64 {
+        let mut agg = T::identity();
+        self.max_right_rec(view, l, &pred, &mut agg).unwrap_or(view.1)
+    }
+
+    fn max_right_rec(
+        &mut self,
+        view: ArqView,
+        l: i64,
+        pred: &impl Fn(&T::S) -> bool,
+        agg: &mut T::S,
+    ) -> Option<i64> {
+        let (p, s) = view;
+        if s - 1 < l {
+            return None;
+        }
+        if l <= 0 {
+            let next = T::op(agg, &self.nodes[p].val);
+            if pred(&next) {
+                *agg = next;
+                return None;
+            } else if s == 1 {
+                return Some(0);
+            }
+        }
+        let (l_view, r_view) = self.push(view);
+        let ls = l_view.1;
+        self.max_right_rec(l_view, l, pred, agg)
+            .or_else(|| self.max_right_rec(r_view, l - ls, pred, agg).map(|x| ls + x))
+    }
+
+    /// Binary searches for the smallest l such that pred(a_l + ... + a_{r-1})
+    /// holds, where pred(identity) must hold. This mirrors max_right().
+    pub fn min_left(&mut self, view: ArqView, r: i64, pred: impl Fn(&T::S) -> bool) -> i64 {
+        let mut agg = T::identity();
+        self.min_left_rec(view, r, &pred, &mut agg).unwrap_or(0)
+    }
+
+    fn min_left_rec(
+        &mut self,
+        view: ArqView,
+        r: i64,
+        pred: &impl Fn(&T::S) -> bool,
+        agg: &mut T::S,
+    ) -> Option<i64> {
+        let (p, s) = view;
+        if r <= 0 {
+            return None;
+        }
+        if s <= r {
+            let next = T::op(&self.nodes[p].val, agg);
+            if pred(&next) {
+                *agg = next;
+                return None;
+            } else if s == 1 {
+                return Some(1);
+            }
+        }
+        let (l_view, r_view) = self.push(view);
+        let ls = l_view.1;
+        self.min_left_rec(r_view, r - ls, pred, agg)
+            .map(|x| ls + x)
+            .or_else(|| self.min_left_rec(l_view, r, pred, agg))
+    }
 }
 
 /// An example of binary search to find the first position whose element is negative.
@@ -1,4 +1,6 @@
-use std::ops::{Add, Sub};
+use std::convert::TryFrom;
+use std::ops::{Add, Mul, Sub};
+use crate::order::SparseIndex;
 
 #[derive(Debug)]
 pub struct FenwickTree<T>{
@@ -13,10 +15,14 @@ impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>> FenwickTree<T>{
         };
     }
     /// Adds the value to the given index.
-    pub fn add(&mut self, mut idx: usize,a: T){
+    pub fn add(&mut self, idx: usize,a: T){
         if idx >= self.n {
             panic!("Index out of bound. length:{}, but idx:{}.", self.n, idx);
         }
+        self.add0(idx, a);
+    }
+    // Adds the value to the given index. Does nothing if idx == n.
+    fn add0(&mut self, mut idx: usize,a: T){
         idx+=1;
         loop {
             if idx > self.n {
@@ -49,6 +55,188 @@ impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>> FenwickTree<T>{
     }
 }
 
+impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>+PartialOrd> FenwickTree<T>{
+    /// Returns the smallest idx such that the summary of values between 0 and
+    /// idx reaches the target, or n if there's no such idx. Runs in O(log n)
+    /// by descending the tree, hence all values must be non-negative.
+    /// With a frequency table, lower_bound(k+1) is the k-th smallest element.
+    pub fn lower_bound(&self, target: T) -> usize {
+        if target <= T::default() {
+            return 0;
+        }
+        let (mut idx, mut rem) = (0, target);
+        // the largest power of two not exceeding n, or 0 if n == 0
+        let mut step = 1;
+        while step <= self.n {
+            step *= 2;
+        }
+        step /= 2;
+        while step > 0 {
+            if idx + step <= self.n && self.bit[idx + step] < rem {
+                idx += step;
+                rem = rem - self.bit[idx];
+            }
+            step >>= 1;
+        }
+        idx
+    }
+}
+
+/// Fenwick tree supporting range add and range sum. It keeps two FenwickTrees
+/// (the dual BIT trick) so that the sum of [0, idx) is b1(idx) * idx - b2(idx).
+#[derive(Debug)]
+pub struct RangeAddFenwickTree<T>{
+    n: usize,
+    b1: FenwickTree<T>,
+    b2: FenwickTree<T>,
+}
+impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>+Mul<Output=T>+TryFrom<usize>> RangeAddFenwickTree<T>{
+    pub fn new(n: usize) -> RangeAddFenwickTree<T> {
+        Self {
+            n,
+            b1: FenwickTree::new(n),
+            b2: FenwickTree::new(n),
+        }
+    }
+    fn of_idx(idx: usize) -> T {
+        T::try_from(idx).ok().expect("The index doesn't fit in the element type.")
+    }
+    /// Adds the value to the indices between l and r-1.
+    pub fn add(&mut self, l: usize, r: usize, a: T){
+        if l>r || r>self.n {
+            panic!("Invalid range. l:{}, r:{}, length:{}", l, r, self.n);
+        }
+        let zero = T::default();
+        self.b1.add0(l, a);
+        self.b1.add0(r, zero-a);
+        self.b2.add0(l, a*Self::of_idx(l));
+        self.b2.add0(r, zero-a*Self::of_idx(r));
+    }
+    /// Returns the summary of values between l and r-1.
+    pub fn sum(&self, l:usize, r:usize) -> T {
+        if l>r {
+            panic!("Invalid range. l:{} > r:{}", l, r);
+        }
+        self.sum0(r) - self.sum0(l)
+    }
+    fn sum0(&self, idx: usize) -> T {
+        self.b1.sum0(idx)*Self::of_idx(idx) - self.b2.sum0(idx)
+    }
+}
+
+/// Two-dimensional Fenwick tree for grids, supporting point add and
+/// rectangle sum.
+#[derive(Debug)]
+pub struct FenwickTree2D<T>{
+    h: usize,
+    w: usize,
+    bit: Vec<Vec<T>>,
+}
+impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>> FenwickTree2D<T>{
+    pub fn new(h: usize, w: usize) -> FenwickTree2D<T> {
+        Self {
+            h,
+            w,
+            bit: vec![vec![T::default(); w+1]; h+1],
+        }
+    }
+    /// Adds the value to the given cell.
+    pub fn add(&mut self, i: usize, j: usize, a: T){
+        if i >= self.h || j >= self.w {
+            panic!("Index out of bound. size:{}x{}, but idx:({},{}).", self.h, self.w, i, j);
+        }
+        let mut i = i+1;
+        while i <= self.h {
+            let mut j = j+1;
+            while j <= self.w {
+                self.bit[i][j] = self.bit[i][j]+a;
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+    /// Returns the summary of values in rows between i1 and i2-1 and columns
+    /// between j1 and j2-1.
+    pub fn sum(&self, i1: usize, j1: usize, i2: usize, j2: usize) -> T {
+        if i1>i2 || j1>j2 {
+            panic!("Invalid range. ({},{}) > ({},{})", i1, j1, i2, j2);
+        }
+        self.sum0(i2,j2) - self.sum0(i1,j2) - self.sum0(i2,j1) + self.sum0(i1,j1)
+    }
+    fn sum0(&self, mut i: usize, j: usize) -> T {
+        let mut ret = T::default();
+        while i > 0 {
+            let mut j = j;
+            while j > 0 {
+                ret = ret+self.bit[i][j];
+                j -= j & j.wrapping_neg();
+            }
+            i -= i & i.wrapping_neg();
+        }
+        ret
+    }
+}
+
+/// Offline two-dimensional Fenwick tree over sparse coordinates. All the
+/// points to be updated must be given in advance; each node of the outer
+/// tree keeps a FenwickTree over the compressed y-coordinates it covers.
+/// O(N log N) space, O(log^2 N) time per operation.
+pub struct SparseFenwickTree2D<T>{
+    xs: SparseIndex,
+    ys: Vec<SparseIndex>,
+    bits: Vec<FenwickTree<T>>,
+}
+impl <T:Clone+Copy+Default+Add<Output=T>+Sub<Output=T>> SparseFenwickTree2D<T>{
+    /// Builds the tree, given the full set of points that will be updated.
+    pub fn new(points: &[(i64, i64)]) -> SparseFenwickTree2D<T> {
+        let xs = SparseIndex::new(points.iter().map(|&(x,_)| x).collect());
+        let mut node_ys = vec![Vec::new(); xs.len()+1];
+        for &(x,y) in points {
+            let mut i = xs.compress(x).unwrap()+1;
+            while i <= xs.len() {
+                node_ys[i].push(y);
+                i += i & i.wrapping_neg();
+            }
+        }
+        let ys = node_ys.into_iter().map(SparseIndex::new).collect::<Vec<_>>();
+        let bits = ys.iter().map(|y| FenwickTree::new(y.len())).collect();
+        Self { xs, ys, bits }
+    }
+    /// Adds the value to the given point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point was not given on construction.
+    pub fn add(&mut self, x: i64, y: i64, a: T){
+        let mut i = self.xs.compress(x)
+            .unwrap_or_else(|_| panic!("Unknown point: ({},{}).", x, y))+1;
+        while i <= self.xs.len() {
+            let j = self.ys[i].compress(y)
+                .unwrap_or_else(|_| panic!("Unknown point: ({},{}).", x, y));
+            self.bits[i].add(j, a);
+            i += i & i.wrapping_neg();
+        }
+    }
+    /// Returns the summary of values at points (x, y) with x1 <= x < x2 and
+    /// y1 <= y < y2.
+    pub fn sum(&self, x1: i64, y1: i64, x2: i64, y2: i64) -> T {
+        if x1>x2 || y1>y2 {
+            panic!("Invalid range. ({},{}) > ({},{})", x1, y1, x2, y2);
+        }
+        self.sum0(x2,y2) - self.sum0(x1,y2) - self.sum0(x2,y1) + self.sum0(x1,y1)
+    }
+    fn sum0(&self, x: i64, y: i64) -> T {
+        let mut ret = T::default();
+        let mut i = self.xs.compress(x).unwrap_or_else(|i| i);
+        while i > 0 {
+            let j = self.ys[i].compress(y).unwrap_or_else(|j| j);
+            ret = ret+self.bits[i].sum0(j);
+            i -= i & i.wrapping_neg();
+        }
+        ret
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -72,4 +260,79 @@ mod test {
         assert_eq!(0,bit.sum(1,2));
         assert_eq!(0,bit.sum(0,0));
     }
+
+    #[test]
+    fn test_fenwick_lower_bound() {
+        let mut bit = FenwickTree::new(7);
+        for &(idx, a) in &[(1, 2), (2, 0), (3, 1), (6, 4)] {
+            bit.add(idx, a);
+        }
+        // prefix sums: 0 2 2 3 3 3 7
+        assert_eq!(0, bit.lower_bound(0));
+        assert_eq!(1, bit.lower_bound(1));
+        assert_eq!(1, bit.lower_bound(2));
+        assert_eq!(3, bit.lower_bound(3));
+        assert_eq!(6, bit.lower_bound(4));
+        assert_eq!(6, bit.lower_bound(7));
+        assert_eq!(7, bit.lower_bound(8));
+        assert_eq!(0, FenwickTree::<i64>::new(0).lower_bound(1));
+    }
+
+    #[test]
+    fn test_range_add_fenwick() {
+        let mut bit = RangeAddFenwickTree::new(10);
+        let mut naive = [0i64; 10];
+        for &(l, r, a) in &[(2, 5, 3), (0, 10, -1), (4, 4, 7), (5, 10, 2), (3, 6, 5)] {
+            bit.add(l, r, a);
+            for v in &mut naive[l..r] {
+                *v += a;
+            }
+            for l in 0..=10 {
+                for r in l..=10 {
+                    assert_eq!(naive[l..r].iter().sum::<i64>(), bit.sum(l, r));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fenwick_2d() {
+        let mut bit = FenwickTree2D::new(3, 4);
+        bit.add(0, 0, 1);
+        bit.add(1, 2, 2);
+        bit.add(2, 3, 4);
+        bit.add(1, 1, -8);
+        assert_eq!(-1, bit.sum(0, 0, 3, 4));
+        assert_eq!(6, bit.sum(0, 2, 3, 4));
+        assert_eq!(-6, bit.sum(1, 1, 2, 3));
+        assert_eq!(1, bit.sum(0, 0, 1, 1));
+        assert_eq!(0, bit.sum(2, 0, 3, 3));
+        assert_eq!(0, bit.sum(1, 1, 1, 3));
+    }
+
+    #[test]
+    fn test_sparse_fenwick_2d() {
+        let points = [(-5, 100), (3, -7), (3, 1_000_000_000), (42, 0), (-5, -7)];
+        let mut bit = SparseFenwickTree2D::new(&points);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            bit.add(x, y, 1 << i);
+        }
+        bit.add(3, -7, 32);
+        for &x1 in &[-10, -5, 0, 3, 4, 50] {
+            for &x2 in &[-10, -5, 0, 3, 4, 50] {
+                for &y1 in &[-10, -7, 0, 100, 1_000_000_000] {
+                    for &y2 in &[-10, -7, 0, 100, 1_000_000_001] {
+                        if x1 > x2 || y1 > y2 {
+                            continue;
+                        }
+                        let expected = points.iter().enumerate()
+                            .filter(|&(_, &(x, y))| x1 <= x && x < x2 && y1 <= y && y < y2)
+                            .map(|(i, &p)| (1 << i) + if p == (3, -7) { 32 } else { 0 })
+                            .sum::<i64>();
+                        assert_eq!(expected, bit.sum(x1, y1, x2, y2));
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,374 @@
+//! Li Chao Trees, maintaining the lower or upper envelope of lines
+use crate::order::SparseIndex;
+
+// Lines are stored as (m, b) pairs, negated in max trees, so that both kinds
+// of trees minimize.
+type Line = (i64, i64);
+
+fn eval((m, b): Line, x: i64) -> i64 {
+    m * x + b
+}
+
+/// A Li Chao tree over a fixed set of x-coordinates, maintaining the minimum
+/// (or maximum) of a collection of lines y = m * x + b, each of which may be
+/// restricted to an interval of x. Inserting a line takes O(log n) time, a
+/// segment O(log^2 n), and evaluating the envelope O(log n), where n is the
+/// number of coordinates.
+///
+/// All arithmetic is exact, so m * x + b must fit in i64.
+pub struct LiChaoTree {
+    index: SparseIndex,
+    lines: Vec<Option<Line>>,
+    sign: i64,
+}
+
+impl LiChaoTree {
+    /// Initializes an empty lower envelope, to be evaluated at the given coordinates.
+    pub fn new_min(coords: Vec<i64>) -> Self {
+        Self::new(coords, 1)
+    }
+
+    /// Initializes an empty upper envelope, to be evaluated at the given coordinates.
+    pub fn new_max(coords: Vec<i64>) -> Self {
+        Self::new(coords, -1)
+    }
+
+    fn new(coords: Vec<i64>, sign: i64) -> Self {
+        let index = SparseIndex::new(coords);
+        let size = index.len().next_power_of_two();
+        Self {
+            index,
+            lines: vec![None; 2 * size],
+            sign,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.lines.len() / 2
+    }
+
+    // Pads the coordinates with copies of the last one up to a power of two.
+    fn coord(&self, i: usize) -> i64 {
+        self.index.decompress(i.min(self.index.len() - 1))
+    }
+
+    /// Inserts the line y = m * x + b.
+    pub fn add_line(&mut self, m: i64, b: i64) {
+        if !self.index.is_empty() {
+            let line = (self.sign * m, self.sign * b);
+            self.insert(1, 0, self.size(), line);
+        }
+    }
+
+    /// Inserts the line y = m * x + b restricted to xl <= x <= xr.
+    pub fn add_segment(&mut self, xl: i64, xr: i64, m: i64, b: i64) {
+        let l = self.index.compress(xl).unwrap_or_else(|i| i);
+        let r = self.index.compress(xr).map_or_else(|i| i, |i| i + 1);
+        let line = (self.sign * m, self.sign * b);
+        self.insert_segment(1, 0, self.size(), l, r, line);
+    }
+
+    fn insert(&mut self, p: usize, lo: usize, hi: usize, mut line: Line) {
+        let mut cur = match self.lines[p] {
+            Some(cur) => cur,
+            None => {
+                self.lines[p] = Some(line);
+                return;
+            }
+        };
+        let mid = (lo + hi) / 2;
+        let x_mid = self.coord(mid);
+        if eval(line, x_mid) < eval(cur, x_mid) {
+            std::mem::swap(&mut line, &mut cur);
+            self.lines[p] = Some(cur);
+        }
+        if hi - lo == 1 {
+            return;
+        }
+        let (x_lo, x_hi) = (self.coord(lo), self.coord(hi - 1));
+        if eval(line, x_lo) < eval(cur, x_lo) {
+            self.insert(p << 1, lo, mid, line);
+        } else if eval(line, x_hi) < eval(cur, x_hi) {
+            self.insert(p << 1 | 1, mid, hi, line);
+        }
+    }
+
+    fn insert_segment(&mut self, p: usize, lo: usize, hi: usize, l: usize, r: usize, line: Line) {
+        if r <= lo || hi <= l {
+            return;
+        }
+        if l <= lo && hi <= r {
+            self.insert(p, lo, hi, line);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.insert_segment(p << 1, lo, mid, l, r, line);
+        self.insert_segment(p << 1 | 1, mid, hi, l, r, line);
+    }
+
+    /// Evaluates the envelope at x, or returns None if no line covers x.
+    ///
+    /// # Panics
+    ///
+    /// Panics if x is not one of the coordinates given on construction.
+    pub fn query(&self, x: i64) -> Option<i64> {
+        let i = self.index.compress(x).expect("Coordinate not in index");
+        let mut p = self.size() + i;
+        let mut best = None;
+        while p > 0 {
+            if let Some(line) = self.lines[p] {
+                let y = eval(line, x);
+                best = Some(best.map_or(y, |b: i64| b.min(y)));
+            }
+            p >>= 1;
+        }
+        best.map(|y| self.sign * y)
+    }
+}
+
+#[derive(Clone)]
+struct LiChaoNode {
+    line: Option<Line>,
+    down: (usize, usize),
+}
+
+/// A Li Chao tree over all integers in an interval, possibly huge, whose
+/// nodes are allocated only along the paths that lines are inserted into.
+/// If persistent, every insertion leaves the tree it was given intact and
+/// returns the root of a new one.
+pub struct DynamicLiChao {
+    nodes: Vec<LiChaoNode>,
+    lo: i64,
+    hi: i64,
+    sign: i64,
+    is_persistent: bool,
+}
+
+impl DynamicLiChao {
+    /// Initializes a lower envelope on lo <= x <= hi, without creating any nodes.
+    pub fn new_min(lo: i64, hi: i64, is_persistent: bool) -> Self {
+        Self::new(lo, hi, 1, is_persistent)
+    }
+
+    /// Initializes an upper envelope on lo <= x <= hi, without creating any nodes.
+    pub fn new_max(lo: i64, hi: i64, is_persistent: bool) -> Self {
+        Self::new(lo, hi, -1, is_persistent)
+    }
+
+    fn new(lo: i64, hi: i64, sign: i64, is_persistent: bool) -> Self {
+        assert!(lo <= hi, "Invalid range.");
+        Self {
+            nodes: vec![],
+            lo,
+            hi,
+            sign,
+            is_persistent,
+        }
+    }
+
+    /// Builds an empty tree and returns its root.
+    pub fn build(&mut self) -> usize {
+        self.new_node(None)
+    }
+
+    fn new_node(&mut self, line: Option<Line>) -> usize {
+        self.nodes.push(LiChaoNode {
+            line,
+            down: (usize::MAX, usize::MAX),
+        });
+        self.nodes.len() - 1
+    }
+
+    fn clone_node(&mut self, p_orig: usize) -> usize {
+        if p_orig == usize::MAX {
+            self.new_node(None)
+        } else if self.is_persistent {
+            let node = self.nodes[p_orig].clone();
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        } else {
+            p_orig
+        }
+    }
+
+    fn mid(lo: i64, hi: i64) -> i64 {
+        ((lo as i128 + hi as i128).div_euclid(2)) as i64
+    }
+
+    /// Inserts the line y = m * x + b into the tree at root, and returns the
+    /// resulting root, which is the same as root unless the tree is persistent.
+    pub fn add_line(&mut self, root: usize, m: i64, b: i64) -> usize {
+        let line = (self.sign * m, self.sign * b);
+        self.insert(root, self.lo, self.hi, line)
+    }
+
+    /// Inserts the line y = m * x + b restricted to xl <= x <= xr; see add_line().
+    pub fn add_segment(&mut self, root: usize, xl: i64, xr: i64, m: i64, b: i64) -> usize {
+        let line = (self.sign * m, self.sign * b);
+        self.insert_segment(root, self.lo, self.hi, xl, xr, line)
+    }
+
+    fn insert(&mut self, p: usize, lo: i64, hi: i64, mut line: Line) -> usize {
+        let p = self.clone_node(p);
+        let mut cur = match self.nodes[p].line {
+            Some(cur) => cur,
+            None => {
+                self.nodes[p].line = Some(line);
+                return p;
+            }
+        };
+        let mid = Self::mid(lo, hi);
+        if eval(line, mid) < eval(cur, mid) {
+            std::mem::swap(&mut line, &mut cur);
+            self.nodes[p].line = Some(cur);
+        }
+        if lo == hi {
+            return p;
+        }
+        if eval(line, lo) < eval(cur, lo) {
+            let lp = self.insert(self.nodes[p].down.0, lo, mid, line);
+            self.nodes[p].down.0 = lp;
+        } else if eval(line, hi) < eval(cur, hi) {
+            let rp = self.insert(self.nodes[p].down.1, mid + 1, hi, line);
+            self.nodes[p].down.1 = rp;
+        }
+        p
+    }
+
+    fn insert_segment(&mut self, p: usize, lo: i64, hi: i64, l: i64, r: i64, line: Line) -> usize {
+        if r < lo || hi < l {
+            return p;
+        }
+        if l <= lo && hi <= r {
+            return self.insert(p, lo, hi, line);
+        }
+        let p = self.clone_node(p);
+        let mid = Self::mid(lo, hi);
+        let lp = self.insert_segment(self.nodes[p].down.0, lo, mid, l, r, line);
+        let rp = self.insert_segment(self.nodes[p].down.1, mid + 1, hi, l, r, line);
+        self.nodes[p].down = (lp, rp);
+        p
+    }
+
+    /// Evaluates the envelope of the tree at root at x, or returns None if no
+    /// line covers x.
+    pub fn query(&self, root: usize, x: i64) -> Option<i64> {
+        assert!(self.lo <= x && x <= self.hi, "Index out of bound. range:[{}, {}], but x:{}.", self.lo, self.hi, x);
+        let (mut p, mut lo, mut hi) = (root, self.lo, self.hi);
+        let mut best = None;
+        while p != usize::MAX {
+            if let Some(line) = self.nodes[p].line {
+                let y = eval(line, x);
+                best = Some(best.map_or(y, |b: i64| b.min(y)));
+            }
+            let mid = Self::mid(lo, hi);
+            if x <= mid {
+                p = self.nodes[p].down.0;
+                hi = mid;
+            } else {
+                p = self.nodes[p].down.1;
+                lo = mid + 1;
+            }
+        }
+        best.map(|y| self.sign * y)
+    }
+
+    /// Returns the number of nodes allocated so far.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    fn brute(segments: &[(i64, i64, i64, i64)], x: i64, is_max: bool) -> Option<i64> {
+        let ys = segments.iter().filter(|s| s.0 <= x && x <= s.1).map(|s| s.2 * x + s.3);
+        if is_max {
+            ys.max()
+        } else {
+            ys.min()
+        }
+    }
+
+    #[test]
+    fn test_li_chao() {
+        let mut tree = LiChaoTree::new_min(vec![-3, 0, 2, 5]);
+        assert_eq!(tree.query(0), None);
+        tree.add_line(1, 0);
+        tree.add_line(-1, 1);
+        assert_eq!(tree.query(-3), Some(-3));
+        assert_eq!(tree.query(2), Some(-1));
+        tree.add_segment(1, 3, 0, -5);
+        assert_eq!(tree.query(2), Some(-5));
+        assert_eq!(tree.query(5), Some(-4));
+
+        let mut tree = LiChaoTree::new_max(vec![-3, 0, 2, 5]);
+        tree.add_line(1, 0);
+        tree.add_line(-1, 1);
+        assert_eq!(tree.query(-3), Some(4));
+        assert_eq!(tree.query(5), Some(5));
+    }
+
+    #[test]
+    fn test_li_chao_random() {
+        let mut rng = SmallRng::new(12);
+        let coords = (0..30).map(|_| (rng.next_u64() % 100) as i64 - 50).collect::<Vec<_>>();
+        for &is_max in &[false, true] {
+            let mut tree = if is_max {
+                LiChaoTree::new_max(coords.clone())
+            } else {
+                LiChaoTree::new_min(coords.clone())
+            };
+            let mut dyn_tree = if is_max {
+                DynamicLiChao::new_max(-50, 49, false)
+            } else {
+                DynamicLiChao::new_min(-50, 49, false)
+            };
+            let root = dyn_tree.build();
+            let mut segments = vec![];
+            for _ in 0..200 {
+                let mut r = |k: u64| (rng.next_u64() % k) as i64;
+                let (m, b) = (r(21) - 10, r(201) - 100);
+                if r(2) == 0 {
+                    tree.add_line(m, b);
+                    dyn_tree.add_line(root, m, b);
+                    segments.push((i64::MIN, i64::MAX, m, b));
+                } else {
+                    let (x1, x2) = (r(120) - 60, r(120) - 60);
+                    let (xl, xr) = (x1.min(x2), x1.max(x2));
+                    tree.add_segment(xl, xr, m, b);
+                    dyn_tree.add_segment(root, xl, xr, m, b);
+                    segments.push((xl, xr, m, b));
+                }
+                for &x in &coords {
+                    assert_eq!(tree.query(x), brute(&segments, x, is_max));
+                }
+                for x in -50..50 {
+                    assert_eq!(dyn_tree.query(root, x), brute(&segments, x, is_max));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_persistent_li_chao() {
+        let big = 1_000_000_000;
+        let mut tree = DynamicLiChao::new_max(-big, big, true);
+        let v0 = tree.build();
+        let v1 = tree.add_line(v0, 2, 0);
+        let v2 = tree.add_line(v1, -3, 10);
+        let v3 = tree.add_segment(v1, -5, 5, 0, 100);
+
+        assert_eq!(tree.query(v0, 7), None);
+        assert_eq!(tree.query(v1, -big), Some(-2 * big));
+        assert_eq!(tree.query(v2, -big), Some(3 * big + 10));
+        assert_eq!(tree.query(v2, big), Some(2 * big));
+        assert_eq!(tree.query(v3, 5), Some(100));
+        assert_eq!(tree.query(v3, 6), Some(12));
+        assert_eq!(tree.query(v1, 5), Some(10));
+        assert!(tree.num_nodes() < 200);
+    }
+}
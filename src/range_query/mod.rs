@@ -3,14 +3,59 @@ pub mod specs;
 pub mod sqrt_decomp;
 pub mod static_arq;
 pub mod fenwick;
+pub mod beats_arq;
+pub mod persistent_arq;
+pub mod wavelet_matrix;
+pub mod li_chao;
+pub mod sparse_table;
 pub use dynamic_arq::{ArqView, DynamicArq};
-pub use specs::ArqSpec;
+pub use specs::{ArqSpec, BeatsSpec};
 pub use static_arq::StaticArq;
+pub use beats_arq::BeatsArq;
+pub use persistent_arq::{PersistentArq, Version};
+pub use wavelet_matrix::WaveletMatrix;
+pub use li_chao::{DynamicLiChao, LiChaoTree};
+pub use sparse_table::{DisjointSparseTable, SparseTable};
 
 #[cfg(test)]
 mod test {
     use super::specs::*;
     use super::*;
+    use crate::geometry::Affine;
+    use crate::math::num::Matrix;
+    use crate::rng::SmallRng;
+
+    /// Composition of affine maps, a non-commutative monoid: the aggregate
+    /// of a[l..=r] applies a[l] first. Only point updates are supported.
+    enum ArqAffine {}
+    impl ArqSpec for ArqAffine {
+        type S = Affine;
+        type F = Affine;
+        fn op(a: &Self::S, b: &Self::S) -> Self::S {
+            a.compose(b)
+        }
+        fn identity() -> Self::S {
+            Affine::entity(2)
+        }
+        fn compose(_: &Self::F, _: &Self::F) -> Self::F {
+            unimplemented!()
+        }
+        fn apply(f: &Self::F, _: &Self::S, _: i64) -> Self::S {
+            f.clone()
+        }
+    }
+
+    // Non-negative maps that never shrink a non-negative vector, so that the
+    // L1 norm of the image of (1, 1) is monotone in the range.
+    fn random_affine(rng: &mut SmallRng) -> Affine {
+        let mut r = |lo: u64| (lo + rng.next_u64() % 2) as f64;
+        let a = Matrix::from(vec![vec![r(1), r(0)], vec![r(0), r(1)]]);
+        Affine::new(a, vec![r(0), r(0)])
+    }
+
+    fn norm(f: &Affine) -> f64 {
+        f.transform(&vec![1., 1.]).iter().sum()
+    }
 
     #[test]
     fn test_rmq() {
@@ -108,6 +153,26 @@ mod test {
         assert_eq!(arq.query(view, 10, 4), 0);
     }
 
+    #[test]
+    fn test_range_add_sum() {
+        let mut rng = SmallRng::new(15);
+        for n in 1..40 {
+            let mut vec = vec![0; n];
+            let mut arq = StaticArq::<ArqAddSum>::new(&vec);
+            for _ in 0..100 {
+                let a = rng.next_u64() as usize % n;
+                let b = rng.next_u64() as usize % n;
+                let (l, r) = (a.min(b), a.max(b));
+                if rng.next_u64() % 2 == 0 {
+                    arq.update(l, r, &1);
+                    vec[l..=r].iter_mut().for_each(|x| *x += 1);
+                } else {
+                    assert_eq!(arq.query(l, r), vec[l..=r].iter().sum::<i64>());
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_supply_demand() {
         let mut arq = StaticArq::<ArqSupplyDemand>::new(&[(0, 0, 0); 10]);
@@ -157,4 +222,72 @@ mod test {
         assert_eq!(first_neg, Some(3));
         assert_eq!(first_neg_zeros, None);
     }
+
+    #[test]
+    fn test_non_commutative_binary_search() {
+        let mut rng = SmallRng::new(7);
+        for n in 1..=20 {
+            let mut vec = (0..n).map(|_| random_affine(&mut rng)).collect::<Vec<_>>();
+            let mut arq = StaticArq::<ArqAffine>::new(&vec);
+            let mut dyn_arq = DynamicArq::<ArqAffine>::new(false);
+            let view = dyn_arq.build_from_slice(&vec);
+
+            for _ in 0..10 {
+                let i = rng.next_u64() as usize % n;
+                vec[i] = random_affine(&mut rng);
+                arq.update(i, i, &vec[i]);
+                dyn_arq.update(view, i as i64, i as i64, &vec[i]);
+
+                let bound = (rng.next_u64() % 200) as f64;
+                let pred = |f: &Affine| norm(f) <= bound;
+                for l in 0..=n {
+                    let mut agg = Affine::entity(2);
+                    let mut expected_r = l;
+                    while expected_r < n && pred(&agg.compose(&vec[expected_r])) {
+                        agg = agg.compose(&vec[expected_r]);
+                        expected_r += 1;
+                    }
+                    if l < n {
+                        let suffix = vec[l..].iter().fold(Affine::entity(2), |a, f| a.compose(f));
+                        assert_eq!(arq.query(l, n - 1), suffix);
+                    }
+                    assert_eq!(arq.max_right(l, pred), expected_r);
+                    assert_eq!(dyn_arq.max_right(view, l as i64, pred), expected_r as i64);
+                }
+                for r in 0..=n {
+                    let mut agg = Affine::entity(2);
+                    let mut expected_l = r;
+                    while expected_l > 0 && pred(&vec[expected_l - 1].compose(&agg)) {
+                        agg = vec[expected_l - 1].compose(&agg);
+                        expected_l -= 1;
+                    }
+                    assert_eq!(arq.min_left(r, pred), expected_l);
+                    assert_eq!(dyn_arq.min_left(view, r as i64, pred), expected_l as i64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_binary_search() {
+        let mut arq = StaticArq::<ArqSum>::new(&[1; 6]);
+        let mut dyn_arq = DynamicArq::<ArqSum>::new(false);
+        let view = dyn_arq.build_from_identity(6);
+        dyn_arq.update(view, 0, 5, &1);
+
+        arq.update(2, 4, &3);
+        dyn_arq.update(view, 2, 4, &3);
+
+        // entries: 1 1 3 3 3 1
+        assert_eq!(arq.max_right(0, |&s| s <= 5), 3);
+        assert_eq!(arq.max_right(1, |&s| s <= 7), 4);
+        assert_eq!(arq.max_right(3, |&s| s <= 7), 6);
+        assert_eq!(arq.min_left(6, |&s| s <= 7), 3);
+        assert_eq!(arq.min_left(2, |&s| s <= 0), 2);
+        assert_eq!(dyn_arq.max_right(view, 0, |&s| s <= 5), 3);
+        assert_eq!(dyn_arq.max_right(view, 1, |&s| s <= 7), 4);
+        assert_eq!(dyn_arq.max_right(view, 3, |&s| s <= 7), 6);
+        assert_eq!(dyn_arq.min_left(view, 6, |&s| s <= 7), 3);
+        assert_eq!(dyn_arq.min_left(view, 2, |&s| s <= 0), 2);
+    }
 }
@@ -0,0 +1,199 @@
+//! Persistent Associative Range Query Tree with typed version handles
+use super::specs::ArqAddSum;
+use super::{ArqSpec, ArqView, DynamicArq};
+
+/// A handle to a version of a PersistentArq.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Version(usize);
+
+/// A persistent DynamicArq that keeps track of its versions: every update
+/// leaves its input version intact and returns a handle to a new one.
+/// Versions that are no longer needed can be released, and the nodes that
+/// only they could reach reclaimed with gc().
+///
+/// # Panics
+///
+/// Methods will panic if given a released version.
+pub struct PersistentArq<T: ArqSpec> {
+    arq: DynamicArq<T>,
+    versions: Vec<Option<ArqView>>,
+}
+
+impl<T: ArqSpec> PersistentArq<T> {
+    /// Builds the initial version with its leaves set to a given non-empty slice.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let mut arq = DynamicArq::new(true);
+        let view = arq.build_from_slice(init_val);
+        Self {
+            arq,
+            versions: vec![Some(view)],
+        }
+    }
+
+    /// Lazily builds the initial version with all its leaves set to the identity.
+    pub fn from_identity(size: i64) -> Self {
+        let mut arq = DynamicArq::new(true);
+        let view = arq.build_from_identity(size);
+        Self {
+            arq,
+            versions: vec![Some(view)],
+        }
+    }
+
+    /// Returns the version created on construction.
+    pub fn initial(&self) -> Version {
+        Version(0)
+    }
+
+    fn view(&self, version: Version) -> ArqView {
+        self.versions[version.0].expect("Version has been released")
+    }
+
+    /// Applies the endomorphism f to all entries from l to r, inclusive, of
+    /// the given version. Returns the resulting new version.
+    pub fn update(&mut self, version: Version, l: i64, r: i64, f: &T::F) -> Version {
+        let view = self.arq.update(self.view(version), l, r, f);
+        self.versions.push(Some(view));
+        Version(self.versions.len() - 1)
+    }
+
+    /// Returns the aggregate range query on all entries from l to r, inclusive,
+    /// of the given version.
+    pub fn query(&mut self, version: Version, l: i64, r: i64) -> T::S {
+        self.arq.query(self.view(version), l, r)
+    }
+
+    /// Binary search on the given version; see DynamicArq::max_right().
+    pub fn max_right(&mut self, version: Version, l: i64, pred: impl Fn(&T::S) -> bool) -> i64 {
+        self.arq.max_right(self.view(version), l, pred)
+    }
+
+    /// Binary search on the given version; see DynamicArq::min_left().
+    pub fn min_left(&mut self, version: Version, r: i64, pred: impl Fn(&T::S) -> bool) -> i64 {
+        self.arq.min_left(self.view(version), r, pred)
+    }
+
+    /// Marks the version as no longer needed. Its nodes are reclaimed by the
+    /// next gc() unless other versions share them.
+    pub fn release(&mut self, version: Version) {
+        self.versions[version.0] = None;
+    }
+
+    /// Discards all nodes that no unreleased version can reach.
+    pub fn gc(&mut self) {
+        let live = self.versions.iter().flatten().copied().collect::<Vec<_>>();
+        let mut relocated = self.arq.retain(&live).into_iter();
+        for view in self.versions.iter_mut().flatten() {
+            *view = relocated.next().unwrap();
+        }
+    }
+
+    /// Returns the number of nodes allocated so far.
+    pub fn num_nodes(&self) -> usize {
+        self.arq.num_nodes()
+    }
+}
+
+impl PersistentArq<ArqAddSum> {
+    /// Regarding entry x as the number of occurrences of the value x, returns
+    /// the k-th smallest value (0-indexed) that occurs more often in version
+    /// hi than in version lo, counting multiplicity, or None if there are at
+    /// most k such occurrences. In the classic application, version i counts
+    /// the values in a[0..i], so that lo and hi select the subarray a[l..r].
+    pub fn kth_between(&mut self, lo: Version, hi: Version, mut k: i64) -> Option<i64> {
+        let (mut lo_view, mut hi_view) = (self.view(lo), self.view(hi));
+        let size = hi_view.1;
+        if self.arq.query(hi_view, 0, size - 1) - self.arq.query(lo_view, 0, size - 1) <= k {
+            return None;
+        }
+        let mut offset = 0;
+        while hi_view.1 > 1 {
+            let (lo_l, lo_r) = self.arq.push(lo_view);
+            let (hi_l, hi_r) = self.arq.push(hi_view);
+            let ls = hi_l.1;
+            let cnt = self.arq.query(hi_l, 0, ls - 1) - self.arq.query(lo_l, 0, ls - 1);
+            if k < cnt {
+                lo_view = lo_l;
+                hi_view = hi_l;
+            } else {
+                k -= cnt;
+                offset += ls;
+                lo_view = lo_r;
+                hi_view = hi_r;
+            }
+        }
+        Some(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::range_query::specs::ArqSum;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_versions() {
+        let mut arq = PersistentArq::<ArqSum>::new(&[0; 8]);
+        let v0 = arq.initial();
+        let v1 = arq.update(v0, 0, 7, &5);
+        let v2 = arq.update(v1, 2, 4, &1);
+        let v3 = arq.update(v1, 4, 7, &-1);
+
+        assert_eq!(arq.query(v1, 0, 1), 10);
+        assert_eq!(arq.query(v2, 0, 7), 28);
+        assert_eq!(arq.query(v3, 0, 7), 16);
+        assert_eq!(arq.query(v0, 0, 7), 0);
+        assert_eq!(arq.query(v1, 0, 7), 40);
+        assert_eq!(arq.max_right(v2, 0, |&s| s <= 12), 4);
+        assert_eq!(arq.min_left(v2, 8, |&s| s <= 16), 4);
+    }
+
+    #[test]
+    fn test_kth_in_subarray() {
+        let mut rng = SmallRng::new(5);
+        let sigma = 10;
+        let a = (0..50).map(|_| (rng.next_u64() % sigma) as i64).collect::<Vec<_>>();
+        let mut arq = PersistentArq::<ArqAddSum>::from_identity(sigma as i64);
+        let mut prefix = vec![arq.initial()];
+        for &x in &a {
+            let last = *prefix.last().unwrap();
+            prefix.push(arq.update(last, x, x, &1));
+        }
+
+        for l in 0..a.len() {
+            for r in l..=a.len() {
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort_unstable();
+                for k in 0..=sorted.len() {
+                    let expected = sorted.get(k).copied();
+                    assert_eq!(arq.kth_between(prefix[l], prefix[r], k as i64), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gc() {
+        let mut arq = PersistentArq::<ArqAddSum>::from_identity(1 << 20);
+        let mut versions = vec![arq.initial()];
+        for i in 0..100 {
+            let last = *versions.last().unwrap();
+            versions.push(arq.update(last, i * 1000, i * 5000, &1));
+        }
+        let before = arq.num_nodes();
+        for &v in &versions[..99] {
+            arq.release(v);
+        }
+        arq.gc();
+        assert!(arq.num_nodes() < before);
+
+        let (v99, v100) = (versions[99], versions[100]);
+        assert_eq!(arq.query(v99, 0, 1 << 20), (0..99).map(|i| i * 4000 + 1).sum::<i64>());
+        assert_eq!(arq.query(v100, 0, 1 << 20), (0..100).map(|i| i * 4000 + 1).sum::<i64>());
+        let v101 = arq.update(v100, 0, 9, &-1);
+        arq.gc();
+        assert_eq!(arq.query(v101, 0, 9), -9);
+        assert_eq!(arq.query(v100, 0, 9), 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! Sparse Tables for static range queries in constant time
+use super::ArqSpec;
+
+/// A static sequence answering range queries in O(1) time after O(n log n)
+/// preprocessing, for an idempotent op(), i.e., op(a, a) = a, such as min,
+/// max or gcd. Only op() and identity() of the ArqSpec are used.
+pub struct SparseTable<T: ArqSpec> {
+    // table[i][j] is the aggregate of a[j..j + 2^i]
+    table: Vec<Vec<T::S>>,
+}
+
+impl<T: ArqSpec> SparseTable<T> {
+    /// Builds the table on top of the given sequence.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let mut table = vec![init_val.to_vec()];
+        let mut w = 1;
+        while 2 * w <= init_val.len() {
+            let prev = table.last().unwrap();
+            let row = (0..prev.len() - w).map(|j| T::op(&prev[j], &prev[j + w])).collect();
+            table.push(row);
+            w *= 2;
+        }
+        Self { table }
+    }
+
+    /// Returns the aggregate range query on all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn query(&self, l: usize, r: usize) -> T::S {
+        if l > r {
+            return T::identity();
+        }
+        let i = floor_log2(r + 1 - l);
+        T::op(&self.table[i][l], &self.table[i][r + 1 - (1 << i)])
+    }
+}
+
+/// A static sequence answering range queries in O(1) time after O(n log n)
+/// preprocessing, for any associative op(), such as sums, products or
+/// compositions. Only op() and identity() of the ArqSpec are used.
+pub struct DisjointSparseTable<T: ArqSpec> {
+    // At level i, the sequence is split into blocks of size 2^(i+1), and
+    // table[i][j] is the aggregate from j to the middle of j's block:
+    // a[j..mid] if j < mid, and a[mid..=j] otherwise.
+    table: Vec<Vec<T::S>>,
+    len: usize,
+}
+
+impl<T: ArqSpec> DisjointSparseTable<T> {
+    /// Builds the table on top of the given sequence.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let len = init_val.len();
+        let size = len.next_power_of_two().max(2);
+        let mut vals = init_val.to_vec();
+        vals.resize(size, T::identity());
+        let mut table = vec![];
+        let mut half = 1;
+        while half < size {
+            let mut row = vals.clone();
+            for mid in (half..size).step_by(2 * half) {
+                for j in (mid - half..mid - 1).rev() {
+                    row[j] = T::op(&vals[j], &row[j + 1]);
+                }
+                for j in mid + 1..mid + half {
+                    row[j] = T::op(&row[j - 1], &vals[j]);
+                }
+            }
+            table.push(row);
+            half *= 2;
+        }
+        Self { table, len }
+    }
+
+    /// Returns the aggregate range query on all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn query(&self, l: usize, r: usize) -> T::S {
+        if l > r {
+            return T::identity();
+        }
+        assert!(r < self.len, "Index out of bound. length:{}, but r:{}.", self.len, r);
+        if l == r {
+            return self.table[0][l].clone();
+        }
+        let i = floor_log2(l ^ r);
+        T::op(&self.table[i][l], &self.table[i][r])
+    }
+}
+
+// Returns the index of the highest set bit of x, which must be positive.
+fn floor_log2(x: usize) -> usize {
+    (std::mem::size_of::<usize>() * 8) - 1 - x.leading_zeros() as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::num::ModU64;
+    use crate::range_query::specs::{ArqMax, ArqMin};
+    use crate::rng::SmallRng;
+
+    type Mod = ModU64<998_244_353>;
+
+    /// Composition of affine maps x -> a * x + b modulo a prime, a
+    /// non-commutative monoid: the aggregate of a[l..=r] applies a[l] first.
+    enum ArqModAffine {}
+    impl ArqSpec for ArqModAffine {
+        type S = (Mod, Mod);
+        type F = ();
+        fn op(&(a, b): &Self::S, &(c, d): &Self::S) -> Self::S {
+            (c * a, c * b + d)
+        }
+        fn identity() -> Self::S {
+            (Mod::new(1), Mod::new(0))
+        }
+        fn compose(_: &Self::F, _: &Self::F) -> Self::F {}
+        fn apply(_: &Self::F, a: &Self::S, _: i64) -> Self::S {
+            *a
+        }
+    }
+
+    /// Sums modulo a prime.
+    enum ArqModSum {}
+    impl ArqSpec for ArqModSum {
+        type S = Mod;
+        type F = ();
+        fn op(&a: &Self::S, &b: &Self::S) -> Self::S {
+            a + b
+        }
+        fn identity() -> Self::S {
+            Mod::new(0)
+        }
+        fn compose(_: &Self::F, _: &Self::F) -> Self::F {}
+        fn apply(_: &Self::F, a: &Self::S, _: i64) -> Self::S {
+            *a
+        }
+    }
+
+    #[test]
+    fn test_sparse_table() {
+        let vals = [3, 1, 4, 1, 5, 9, 2, 6];
+        let min_table = SparseTable::<ArqMin>::new(&vals);
+        let max_table = SparseTable::<ArqMax>::new(&vals);
+
+        assert_eq!(min_table.query(0, 7), 1);
+        assert_eq!(min_table.query(4, 6), 2);
+        assert_eq!(max_table.query(0, 4), 5);
+        assert_eq!(max_table.query(6, 6), 2);
+        assert_eq!(max_table.query(7, 6), ArqMax::identity());
+    }
+
+    #[test]
+    fn test_sparse_tables_random() {
+        let mut rng = SmallRng::new(17);
+        for n in 1..=33 {
+            let vals = (0..n).map(|_| rng.next_u64() % 1_000_000_007).collect::<Vec<_>>();
+            let min_table = SparseTable::<ArqMin>::new(&vals.iter().map(|&v| v as i64).collect::<Vec<_>>());
+            let sums = vals.iter().map(|&v| Mod::new(v)).collect::<Vec<_>>();
+            let sum_table = DisjointSparseTable::<ArqModSum>::new(&sums);
+            let maps = vals.iter().map(|&v| (Mod::new(v), Mod::new(v / 7))).collect::<Vec<_>>();
+            let map_table = DisjointSparseTable::<ArqModAffine>::new(&maps);
+
+            for l in 0..n {
+                for r in l..n {
+                    assert_eq!(min_table.query(l, r), *vals[l..=r].iter().min().unwrap() as i64);
+                    let sum = sums[l..=r].iter().fold(Mod::new(0), |acc, &v| acc + v);
+                    assert_eq!(sum_table.query(l, r), sum);
+                    let composed = maps[l..=r].iter().fold(ArqModAffine::identity(), |acc, f| ArqModAffine::op(&acc, f));
+                    assert_eq!(map_table.query(l, r), composed);
+                }
+            }
+        }
+    }
+}
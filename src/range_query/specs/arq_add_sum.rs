@@ -0,0 +1,26 @@
+use super::ArqSpec;
+
+/// Range Sum Query with range addition.
+/// update(l, r, &f) adds f to all entries a[l..=r].
+/// query(l, r) sums all the entries a[l..=r].
+///
+/// # Panics
+///
+/// Associated functions will panic on overflow.
+pub enum ArqAddSum {}
+impl ArqSpec for ArqAddSum {
+    type S = i64;
+    type F = i64;
+    fn op(&a: &Self::S, &b: &Self::S) -> Self::S {
+        a + b
+    }
+    fn identity() -> Self::S {
+        0
+    }
+    fn compose(&f: &Self::F, &g: &Self::F) -> Self::F {
+        f + g
+    }
+    fn apply(&f: &Self::F, &a: &Self::S, size: i64) -> Self::S {
+        a + f * size
+    }
+}
@@ -0,0 +1,149 @@
+use super::BeatsSpec;
+
+/// Aggregate of a range for ArqChminChmaxSum. Besides the sum, it keeps the
+/// largest value with its multiplicity and the strict runner-up, and likewise
+/// for the smallest value. Runner-ups of a range with a single distinct value
+/// are i64::MIN and i64::MAX, respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SumMinMax {
+    pub sum: i64,
+    pub max: i64,
+    pub max2: i64,
+    pub max_cnt: i64,
+    pub min: i64,
+    pub min2: i64,
+    pub min_cnt: i64,
+}
+
+/// Range chmin, chmax and add with range sum, min and max queries: the
+/// classic application of segment tree beats, in O(log^2 n) amortized time.
+/// update(l, r, &(add, lo, hi)) replaces each a[i] (l <= i <= r) by
+/// min(max(a[i] + add, lo), hi). Use chmin(), chmax() and add() to build it.
+/// query(l, r) returns the SumMinMax of a[l..=r].
+///
+/// # Panics
+///
+/// Associated functions will panic on overflow.
+//
+// Functions of the form a -> min(max(a + add, lo), hi) with lo <= hi are
+// closed under composition, so a single lazy tag represents any sequence of
+// updates. i64::MIN and i64::MAX stand for "no bound" and are never shifted.
+pub enum ArqChminChmaxSum {}
+impl ArqChminChmaxSum {
+    /// Builds the aggregate of a single entry.
+    pub fn leaf(v: i64) -> SumMinMax {
+        SumMinMax {
+            sum: v,
+            max: v,
+            max2: i64::MIN,
+            max_cnt: 1,
+            min: v,
+            min2: i64::MAX,
+            min_cnt: 1,
+        }
+    }
+    /// a[i] = min(a[i], x)
+    pub fn chmin(x: i64) -> (i64, i64, i64) {
+        (0, i64::MIN, x)
+    }
+    /// a[i] = max(a[i], x)
+    pub fn chmax(x: i64) -> (i64, i64, i64) {
+        (0, x, i64::MAX)
+    }
+    /// a[i] = a[i] + x
+    pub fn add(x: i64) -> (i64, i64, i64) {
+        (x, i64::MIN, i64::MAX)
+    }
+    fn shift(a: i64, add: i64) -> i64 {
+        if a == i64::MIN || a == i64::MAX {
+            a
+        } else {
+            a + add
+        }
+    }
+}
+impl BeatsSpec for ArqChminChmaxSum {
+    type S = SumMinMax;
+    type F = (i64, i64, i64); // add, lo, hi
+    fn op(a: &Self::S, b: &Self::S) -> Self::S {
+        let (max, max2, max_cnt) = if a.max == b.max {
+            (a.max, a.max2.max(b.max2), a.max_cnt + b.max_cnt)
+        } else if a.max > b.max {
+            (a.max, a.max2.max(b.max), a.max_cnt)
+        } else {
+            (b.max, b.max2.max(a.max), b.max_cnt)
+        };
+        let (min, min2, min_cnt) = if a.min == b.min {
+            (a.min, a.min2.min(b.min2), a.min_cnt + b.min_cnt)
+        } else if a.min < b.min {
+            (a.min, a.min2.min(b.min), a.min_cnt)
+        } else {
+            (b.min, b.min2.min(a.min), b.min_cnt)
+        };
+        SumMinMax {
+            sum: a.sum + b.sum,
+            max,
+            max2,
+            max_cnt,
+            min,
+            min2,
+            min_cnt,
+        }
+    }
+    fn identity() -> Self::S {
+        SumMinMax {
+            sum: 0,
+            max: i64::MIN,
+            max2: i64::MIN,
+            max_cnt: 0,
+            min: i64::MAX,
+            min2: i64::MAX,
+            min_cnt: 0,
+        }
+    }
+    fn compose(&(f_add, f_lo, f_hi): &Self::F, &(g_add, g_lo, g_hi): &Self::F) -> Self::F {
+        let lo = Self::shift(g_lo, f_add).max(f_lo).min(f_hi);
+        let hi = Self::shift(g_hi, f_add).max(f_lo).min(f_hi);
+        (f_add + g_add, lo, hi)
+    }
+    fn apply(&(add, lo, hi): &Self::F, a: &Self::S, size: i64) -> Option<Self::S> {
+        let mut a = *a;
+        if add != 0 {
+            a.sum += add * size;
+            a.max = Self::shift(a.max, add);
+            a.max2 = Self::shift(a.max2, add);
+            a.min = Self::shift(a.min, add);
+            a.min2 = Self::shift(a.min2, add);
+        }
+        if a.min < lo {
+            // the tag condition: only the smallest values are affected,
+            // which always holds if there's a single distinct value
+            if lo >= a.min2 && a.min != a.max {
+                return None;
+            }
+            // subtracting first keeps extreme values from overflowing
+            a.sum = a.sum - a.min * a.min_cnt + lo * a.min_cnt;
+            if a.max == a.min {
+                a.max = lo;
+            } else if a.max2 == a.min {
+                a.max2 = lo;
+            }
+            a.min = lo;
+        }
+        if a.max > hi {
+            // the tag condition: only the largest values are affected,
+            // which always holds if there's a single distinct value
+            if hi <= a.max2 && a.min != a.max {
+                return None;
+            }
+            a.sum = a.sum - a.max * a.max_cnt + hi * a.max_cnt;
+            if a.min == a.max {
+                a.min = hi;
+            } else if a.min2 == a.max {
+                a.min2 = hi;
+            }
+            a.max = hi;
+        }
+        Some(a)
+    }
+}
@@ -0,0 +1,20 @@
+use super::ArqSpec;
+
+/// Concatenation of vertex lists, with no-op updates, for tests: query(u, v)
+/// lists the vertices of a range or path in order, which checks the order
+/// in which aggregates are combined.
+pub enum ArqPath {}
+impl ArqSpec for ArqPath {
+    type S = Vec<usize>;
+    type F = ();
+    fn op(a: &Self::S, b: &Self::S) -> Self::S {
+        a.iter().chain(b).copied().collect()
+    }
+    fn identity() -> Self::S {
+        vec![]
+    }
+    fn compose(_: &Self::F, _: &Self::F) -> Self::F {}
+    fn apply(_: &Self::F, a: &Self::S, _: i64) -> Self::S {
+        a.clone()
+    }
+}
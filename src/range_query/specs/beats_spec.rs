@@ -0,0 +1,25 @@
+/// Specification for the updates and queries of a BeatsArq. It resembles
+/// ArqSpec, except that apply() is allowed to fail on aggregates that don't
+/// carry enough information to compute their image under f. Segment tree
+/// beats then breaks the update down to the node's children, as in the
+/// "Ji Driver Segment Tree" (https://codeforces.com/blog/entry/57319).
+pub trait BeatsSpec {
+    /// Type of underlying array elements.
+    type S: Clone;
+    /// Type of data representing an update.
+    type F: Clone;
+
+    /// Must satisfy the Associative Law:
+    /// For all a,b,c, op(a, op(b, c)) = op(op(a, b), c)
+    fn op(a: &Self::S, b: &Self::S) -> Self::S;
+    /// Must satisfy the Identity Law:
+    /// For all a, op(a, identity()) = op(identity(), a) = a
+    fn identity() -> Self::S;
+    /// Must satisfy the Composition Law wherever apply() succeeds:
+    /// For all f,g,a, apply(compose(f, g), a) = apply(f, apply(g, a))
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F;
+    /// Returns Some(f(a)) where a is the aggregate of `size` entries, or None
+    /// if f(a) can't be computed from a alone (i.e., the tag condition fails).
+    /// Must never fail on leaves, i.e., with size == 1.
+    fn apply(f: &Self::F, a: &Self::S, size: i64) -> Option<Self::S>;
+}
@@ -10,5 +10,15 @@ mod arq_max;
 pub use arq_max::*;
 mod arq_sum;
 pub use arq_sum::*;
+mod arq_add_sum;
+pub use arq_add_sum::*;
 mod arq_supply_demand;
 pub use arq_supply_demand::*;
+mod beats_spec;
+pub use beats_spec::*;
+mod arq_chmin_chmax_sum;
+pub use arq_chmin_chmax_sum::*;
+#[cfg(test)]
+mod arq_path;
+#[cfg(test)]
+pub use arq_path::*;
@@ -1,3 +1,5 @@
+use crate::graph::lca::Lca;
+
 /// A generic implementation of Mo's algorithm, aka Query Sqrt Decomposition.
 /// It answers q offline queries over intervals in 0..n by shifting the query
 /// interval's endpoints by one position at a time.
@@ -22,40 +24,67 @@ pub trait MoState {
     /// After initializing self to a state corresponding to an empty interval,
     /// call this function to answer all your queries.
     fn process(&mut self, queries: &[(usize, usize, Self::Q)]) -> Vec<Self::A> {
+        let intervals = queries.iter().map(|&(l, r, _)| (l, r)).collect::<Vec<_>>();
+        let order = bucket_order(&intervals, Self::L_R_RATIO);
+        sweep(self, &intervals, &order, |state, i| state.query(&queries[i].2))
+    }
+
+    /// Same as process(), but visits the queries along a Hilbert curve over
+    /// the (l, r) plane, which is usually faster in practice. L_R_RATIO is
+    /// ignored.
+    fn process_hilbert(&mut self, queries: &[(usize, usize, Self::Q)]) -> Vec<Self::A> {
+        let intervals = queries.iter().map(|&(l, r, _)| (l, r)).collect::<Vec<_>>();
+        let order = hilbert_order(&intervals);
+        sweep(self, &intervals, &order, |state, i| state.query(&queries[i].2))
+    }
+}
+
+/// Mo's algorithm with a time dimension, for point updates interleaved with
+/// the queries. Each query also names the number of updates that precede it,
+/// and the state moves back and forth in time by applying and undoing them.
+/// With n positions, q queries and u updates, the total cost is
+/// O(n^(2/3) * (q + u)^(1/3) * q) operations for q ~ u.
+pub trait MoUpdateState: MoState {
+    type U;
+
+    /// Applies an update while the current interval is [l, r].
+    fn apply_update(&mut self, upd: &Self::U, l: usize, r: usize);
+    /// Undoes an update, the most recently applied one, while the current
+    /// interval is [l, r].
+    fn undo_update(&mut self, upd: &Self::U, l: usize, r: usize);
+
+    /// After initializing self to a state corresponding to an empty interval
+    /// and no updates, call this function to answer queries (l, r, t, q), where
+    /// q is asked on [l, r] after applying updates[0..t].
+    fn process_with_updates(&mut self, updates: &[Self::U], queries: &[(usize, usize, usize, Self::Q)]) -> Vec<Self::A> {
         let q = queries.len();
         let mut q_positions: Vec<usize> = (0..q).collect();
-        if let Some(max_r) = queries.iter().map(|&(_, r, _)| r).max() {
-            let q_adjusted = q as f64 * Self::L_R_RATIO;
-            let bucket_width = 1 + max_r / q_adjusted.sqrt() as usize;
+        if let Some(max_r) = queries.iter().map(|&(_, r, _, _)| r).max() {
+            let n = (max_r + 1) as f64;
+            let block = n * n * updates.len().max(1) as f64 / q as f64;
+            let bucket_width = (block.cbrt() as usize).max(1);
             q_positions.sort_unstable_by_key(|&i| {
-                let (l, mut r) = (queries[i].0, queries[i].1);
-                let bucket = l / bucket_width;
-                if bucket % 2 != 0 {
-                    r = max_r - r;
+                let (l, r, mut t, _) = queries[i];
+                let (l_bucket, r_bucket) = (l / bucket_width, r / bucket_width);
+                if (l_bucket + r_bucket) % 2 != 0 {
+                    t = updates.len() - t;
                 }
-                (bucket, r)
+                (l_bucket, r_bucket, t)
             });
         }
 
-        let (mut cur_l, mut cur_r) = (1, 0);
-        let mut answers = Vec::with_capacity(queries.len());
+        let (mut cur_l, mut cur_r, mut cur_t) = (1, 0, 0);
+        let mut answers = Vec::with_capacity(q);
         for i in q_positions {
-            let (l, r, ref q) = queries[i];
-            while cur_l > l {
-                cur_l -= 1;
-                self.insert_left(cur_l);
-            }
-            while cur_r < r {
-                cur_r += 1;
-                self.insert_right(cur_r);
+            let (l, r, t, ref q) = queries[i];
+            move_to(self, &mut cur_l, &mut cur_r, l, r);
+            while cur_t < t {
+                self.apply_update(&updates[cur_t], l, r);
+                cur_t += 1;
             }
-            while cur_l < l {
-                self.remove_left(cur_l);
-                cur_l += 1;
-            }
-            while cur_r > r {
-                self.remove_right(cur_r);
-                cur_r -= 1;
+            while cur_t > t {
+                cur_t -= 1;
+                self.undo_update(&updates[cur_t], l, r);
             }
             answers.push((i, self.query(q)));
         }
@@ -64,6 +93,184 @@ pub trait MoState {
     }
 }
 
+/// Mo's algorithm on the paths of a tree given by its adjacency lists. The
+/// state's positions are vertices: insert_left(v) and remove_left(v) add and
+/// remove vertex v, and each query (u, v, q) is asked on the set of vertices
+/// along the path from u to v. Paths are mapped to intervals of an Euler tour
+/// in which every vertex appears twice, once on entry and once on exit.
+pub fn process_tree<S: MoState>(
+    state: &mut S,
+    adj: &[Vec<usize>],
+    root: usize,
+    queries: &[(usize, usize, S::Q)],
+) -> Vec<S::A> {
+    let n = adj.len();
+    let mut lca = Lca::new(n);
+    lca.init_from(adj, root);
+
+    let (mut tin, mut tout) = (vec![0; n], vec![0; n]);
+    let mut tour = Vec::with_capacity(2 * n);
+    let mut stack = vec![(root, n, 0)];
+    tin[root] = 0;
+    tour.push(root);
+    while let Some((u, p, i)) = stack.pop() {
+        if let Some(&v) = adj[u].get(i) {
+            stack.push((u, p, i + 1));
+            if v != p {
+                tin[v] = tour.len();
+                tour.push(v);
+                stack.push((v, u, 0));
+            }
+        } else {
+            tout[u] = tour.len();
+            tour.push(u);
+        }
+    }
+
+    let mut extra = Vec::with_capacity(queries.len());
+    let intervals = queries
+        .iter()
+        .map(|&(u, v, _)| {
+            let (u, v) = if tin[u] <= tin[v] { (u, v) } else { (v, u) };
+            let w = lca.lca(u, v);
+            if w == u {
+                extra.push(None);
+                (tin[u], tin[v])
+            } else {
+                extra.push(Some(w));
+                (tout[u], tin[v])
+            }
+        })
+        .collect::<Vec<_>>();
+    let order = bucket_order(&intervals, S::L_R_RATIO);
+    let mut toggle = EulerToggle { state, tour, inside: vec![false; n] };
+    sweep(&mut toggle, &intervals, &order, |toggle, i| match extra[i] {
+        Some(w) => {
+            toggle.state.insert_left(w);
+            let ans = toggle.state.query(&queries[i].2);
+            toggle.state.remove_left(w);
+            ans
+        }
+        None => toggle.state.query(&queries[i].2),
+    })
+}
+
+// Positions of an Euler tour, where a vertex is in the set if it appears
+// exactly once in the interval.
+struct EulerToggle<'a, S> {
+    state: &'a mut S,
+    tour: Vec<usize>,
+    inside: Vec<bool>,
+}
+
+impl<S: MoState> MoState for EulerToggle<'_, S> {
+    type Q = S::Q;
+    type A = S::A;
+
+    fn query(&self, q: &Self::Q) -> Self::A {
+        self.state.query(q)
+    }
+    fn insert_left(&mut self, pos: usize) {
+        let v = self.tour[pos];
+        if self.inside[v] {
+            self.state.remove_left(v);
+        } else {
+            self.state.insert_left(v);
+        }
+        self.inside[v] ^= true;
+    }
+    fn remove_left(&mut self, pos: usize) {
+        self.insert_left(pos);
+    }
+}
+
+// Sorts queries into buckets of their left endpoint, alternating the
+// direction of the right endpoint between buckets.
+fn bucket_order(intervals: &[(usize, usize)], l_r_ratio: f64) -> Vec<usize> {
+    let q = intervals.len();
+    let mut q_positions: Vec<usize> = (0..q).collect();
+    if let Some(max_r) = intervals.iter().map(|&(_, r)| r).max() {
+        let q_adjusted = q as f64 * l_r_ratio;
+        let bucket_width = 1 + max_r / q_adjusted.sqrt() as usize;
+        q_positions.sort_unstable_by_key(|&i| {
+            let (l, mut r) = intervals[i];
+            let bucket = l / bucket_width;
+            if bucket % 2 != 0 {
+                r = max_r - r;
+            }
+            (bucket, r)
+        });
+    }
+    q_positions
+}
+
+// Sorts queries by their distance along a Hilbert curve.
+fn hilbert_order(intervals: &[(usize, usize)]) -> Vec<usize> {
+    let max_r = intervals.iter().map(|&(_, r)| r).max().unwrap_or(0) as u64;
+    let side = (max_r + 1).next_power_of_two();
+    let dist = |(x, y): (usize, usize)| {
+        let (mut x, mut y) = (x as u64, y as u64);
+        let mut d = 0;
+        let mut s = side / 2;
+        while s > 0 {
+            let rx = (x & s > 0) as u64;
+            let ry = (y & s > 0) as u64;
+            d += s * s * ((3 * rx) ^ ry);
+            if ry == 0 {
+                if rx == 1 {
+                    x = side - 1 - x;
+                    y = side - 1 - y;
+                }
+                std::mem::swap(&mut x, &mut y);
+            }
+            s /= 2;
+        }
+        d
+    };
+    let mut q_positions: Vec<usize> = (0..intervals.len()).collect();
+    q_positions.sort_by_cached_key(|&i| dist(intervals[i]));
+    q_positions
+}
+
+// Moves the current interval [cur_l, cur_r] to [l, r].
+fn move_to<S: MoState + ?Sized>(state: &mut S, cur_l: &mut usize, cur_r: &mut usize, l: usize, r: usize) {
+    while *cur_l > l {
+        *cur_l -= 1;
+        state.insert_left(*cur_l);
+    }
+    while *cur_r < r {
+        *cur_r += 1;
+        state.insert_right(*cur_r);
+    }
+    while *cur_l < l {
+        state.remove_left(*cur_l);
+        *cur_l += 1;
+    }
+    while *cur_r > r {
+        state.remove_right(*cur_r);
+        *cur_r -= 1;
+    }
+}
+
+// Visits the intervals in the given order, answering each with the state
+// moved onto it.
+fn sweep<S: MoState + ?Sized>(
+    state: &mut S,
+    intervals: &[(usize, usize)],
+    order: &[usize],
+    mut answer: impl FnMut(&mut S, usize) -> S::A,
+) -> Vec<S::A> {
+    let (mut cur_l, mut cur_r) = (1, 0);
+    let mut answers = Vec::with_capacity(order.len());
+    for &i in order {
+        let (l, r) = intervals[i];
+        move_to(state, &mut cur_l, &mut cur_r, l, r);
+        answers.push((i, answer(state, i)));
+    }
+    answers.sort_unstable_by_key(|&(i, _)| i);
+    answers.into_iter().map(|(_, ans)| ans).collect()
+}
+
 pub struct DistinctVals {
     vals: Vec<usize>,
     counts: Vec<usize>,
@@ -101,9 +308,373 @@ impl MoState for DistinctVals {
     }
 }
 
+/// Point updates (pos, old value, new value).
+impl MoUpdateState for DistinctVals {
+    type U = (usize, usize, usize);
+    fn apply_update(&mut self, &(pos, old, new): &Self::U, l: usize, r: usize) {
+        if new >= self.counts.len() {
+            self.counts.resize(new + 1, 0);
+        }
+        let inside = l <= pos && pos <= r;
+        if inside {
+            self.remove_left(pos);
+        }
+        debug_assert_eq!(self.vals[pos], old);
+        self.vals[pos] = new;
+        if inside {
+            self.insert_left(pos);
+        }
+    }
+    fn undo_update(&mut self, &(pos, old, new): &Self::U, l: usize, r: usize) {
+        self.apply_update(&(pos, new, old), l, r);
+    }
+}
+
+/// The specification of a SqrtBlocks: which elements it stores, which
+/// updates it applies to them, and how each block summarizes its elements to
+/// answer queries on it as a whole.
+pub trait BlockSpec {
+    /// the type of elements
+    type S: Clone;
+    /// the type of updates, which also serve as lazy tags of blocks
+    type F: Clone;
+    /// the type of block summaries
+    type B;
+    /// the type of query parameters
+    type Q;
+    /// the type of query answers
+    type A;
+
+    /// Summarizes the elements of a block.
+    fn build(elems: &[Self::S]) -> Self::B;
+    /// Applies the update f to an element.
+    fn apply(f: &Self::F, a: &Self::S) -> Self::S;
+    /// Composes two updates into one: applying the result is the same as
+    /// applying g and then f.
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F;
+    /// Answers a query on a single element.
+    fn query_elem(q: &Self::Q, a: &Self::S) -> Self::A;
+    /// Answers a query on a whole block, as summarized before the lazy tag.
+    fn query_block(q: &Self::Q, block: &Self::B, tag: Option<&Self::F>) -> Self::A;
+    /// Combines the answers of two adjacent ranges.
+    fn op(a: &Self::A, b: &Self::A) -> Self::A;
+    /// The answer on an empty range.
+    fn identity() -> Self::A;
+}
+
+/// Sqrt decomposition of an array into blocks, for range updates and range
+/// queries that no segment tree can aggregate, such as counting the elements
+/// less than x in a range after range additions.
+/// Updates and queries cost O(n / b) block operations plus O(b) element
+/// operations, where b is the block size; a partial update also rebuilds
+/// its block.
+pub struct SqrtBlocks<T: BlockSpec> {
+    vals: Vec<T::S>,
+    blocks: Vec<T::B>,
+    tags: Vec<Option<T::F>>,
+    block_size: usize,
+}
+
+impl<T: BlockSpec> SqrtBlocks<T> {
+    /// Initializes blocks of about sqrt(n) elements each.
+    pub fn new(init_val: &[T::S]) -> Self {
+        let block_size = (init_val.len() as f64).sqrt().ceil() as usize;
+        Self::with_block_size(init_val, block_size)
+    }
+
+    /// Initializes blocks of the given size.
+    pub fn with_block_size(init_val: &[T::S], block_size: usize) -> Self {
+        let block_size = block_size.max(1);
+        let blocks = init_val.chunks(block_size).map(T::build).collect::<Vec<_>>();
+        let tags = blocks.iter().map(|_| None).collect();
+        Self {
+            vals: init_val.to_vec(),
+            blocks,
+            tags,
+            block_size,
+        }
+    }
+
+    /// Gets the number of elements.
+    pub fn len(&self) -> usize {
+        self.vals.len()
+    }
+
+    /// Returns true if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.vals.is_empty()
+    }
+
+    /// Gets the element at the specified index.
+    pub fn get(&self, idx: usize) -> T::S {
+        match self.tags[idx / self.block_size] {
+            Some(ref f) => T::apply(f, &self.vals[idx]),
+            None => self.vals[idx].clone(),
+        }
+    }
+
+    fn block_range(&self, b: usize) -> std::ops::Range<usize> {
+        b * self.block_size..self.vals.len().min((b + 1) * self.block_size)
+    }
+
+    fn check_range(&self, l: usize, r: usize) {
+        if l <= r && r >= self.vals.len() {
+            panic!("Index out of bound. length:{}, but r:{}.", self.vals.len(), r);
+        }
+    }
+
+    /// Applies the update f to all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn update(&mut self, l: usize, r: usize, f: &T::F) {
+        self.check_range(l, r);
+        if l > r {
+            return;
+        }
+        let (lb, rb) = (l / self.block_size, r / self.block_size);
+        for b in lb..=rb {
+            let range = self.block_range(b);
+            if l <= range.start && range.end <= r + 1 {
+                let h = match self.tags[b] {
+                    Some(ref g) => T::compose(f, g),
+                    None => f.clone(),
+                };
+                self.tags[b] = Some(h);
+            } else {
+                let tag = self.tags[b].take();
+                for i in range.clone() {
+                    if let Some(ref g) = tag {
+                        self.vals[i] = T::apply(g, &self.vals[i]);
+                    }
+                    if l <= i && i <= r {
+                        self.vals[i] = T::apply(f, &self.vals[i]);
+                    }
+                }
+                self.blocks[b] = T::build(&self.vals[range]);
+            }
+        }
+    }
+
+    /// Answers the query q on all entries from l to r, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if r >= size. Note that l > r is valid, meaning an empty range.
+    pub fn query(&self, l: usize, r: usize, q: &T::Q) -> T::A {
+        self.check_range(l, r);
+        let mut ans = T::identity();
+        if l > r {
+            return ans;
+        }
+        let (lb, rb) = (l / self.block_size, r / self.block_size);
+        for b in lb..=rb {
+            let range = self.block_range(b);
+            if l <= range.start && range.end <= r + 1 {
+                ans = T::op(&ans, &T::query_block(q, &self.blocks[b], self.tags[b].as_ref()));
+            } else {
+                for i in range.start.max(l)..range.end.min(r + 1) {
+                    ans = T::op(&ans, &T::query_elem(q, &self.get(i)));
+                }
+            }
+        }
+        ans
+    }
+}
+
+/// Range addition, and counting the elements less than x in a range.
+pub enum AddCountLess {}
+impl BlockSpec for AddCountLess {
+    type S = i64;
+    type F = i64;
+    type B = Vec<i64>;
+    type Q = i64;
+    type A = usize;
+    fn build(elems: &[Self::S]) -> Self::B {
+        let mut sorted = elems.to_vec();
+        sorted.sort_unstable();
+        sorted
+    }
+    fn apply(&f: &Self::F, &a: &Self::S) -> Self::S {
+        a + f
+    }
+    fn compose(&f: &Self::F, &g: &Self::F) -> Self::F {
+        f + g
+    }
+    fn query_elem(&x: &Self::Q, &a: &Self::S) -> Self::A {
+        (a < x) as usize
+    }
+    fn query_block(&x: &Self::Q, block: &Self::B, tag: Option<&Self::F>) -> Self::A {
+        let x = x - tag.copied().unwrap_or(0);
+        // lower bound: the number of sorted elements below x
+        let (mut lo, mut hi) = (0, block.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if block[mid] < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+    fn op(&a: &Self::A, &b: &Self::A) -> Self::A {
+        a + b
+    }
+    fn identity() -> Self::A {
+        0
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rng::SmallRng;
+
+    fn distinct(vals: &[usize]) -> usize {
+        vals.iter().collect::<std::collections::HashSet<_>>().len()
+    }
+
+    fn random_queries(rng: &mut SmallRng, n: usize, q: usize) -> Vec<(usize, usize, ())> {
+        (0..q)
+            .map(|_| {
+                let a = rng.next_u64() as usize % n;
+                let b = rng.next_u64() as usize % n;
+                (a.min(b), a.max(b), ())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sqrt_blocks() {
+        let mut blocks = SqrtBlocks::<AddCountLess>::with_block_size(&[5, 1, 4, 2, 3, 0, 6], 3);
+
+        assert_eq!(blocks.query(0, 6, &3), 3);
+        blocks.update(1, 5, &2);
+        // entries: 5 3 6 4 5 2 6
+        assert_eq!(blocks.query(0, 6, &5), 3);
+        assert_eq!(blocks.query(2, 4, &5), 1);
+        blocks.update(0, 6, &-1);
+        assert_eq!(blocks.get(5), 1);
+        assert_eq!(blocks.query(3, 6, &5), 3);
+        assert_eq!(blocks.query(4, 3, &5), 0);
+    }
+
+    #[test]
+    fn test_sqrt_blocks_random() {
+        let mut rng = SmallRng::new(11);
+        let n = 40;
+        let mut naive = (0..n).map(|_| (rng.next_u64() % 50) as i64).collect::<Vec<_>>();
+        for &block_size in &[1, 4, 7, 40, 100] {
+            let mut blocks = SqrtBlocks::<AddCountLess>::with_block_size(&naive, block_size);
+            for _ in 0..500 {
+                let a = rng.next_u64() as usize % n;
+                let b = rng.next_u64() as usize % n;
+                let (l, r) = (a.min(b), a.max(b));
+                let x = (rng.next_u64() % 50) as i64 - 20;
+                if rng.next_u64() % 2 == 0 {
+                    blocks.update(l, r, &x);
+                    naive[l..=r].iter_mut().for_each(|v| *v += x);
+                } else {
+                    let expected = naive[l..=r].iter().filter(|&&v| v < x).count();
+                    assert_eq!(blocks.query(l, r, &x), expected);
+                }
+            }
+            assert_eq!((0..n).map(|i| blocks.get(i)).collect::<Vec<_>>(), naive);
+        }
+    }
+
+    #[test]
+    fn test_mos_algorithm_hilbert() {
+        let mut rng = SmallRng::new(8);
+        let arr = (0..100).map(|_| rng.next_u64() as usize % 20).collect::<Vec<_>>();
+        let queries = random_queries(&mut rng, arr.len(), 300);
+
+        let expected = queries.iter().map(|&(l, r, _)| distinct(&arr[l..=r])).collect::<Vec<_>>();
+        assert_eq!(DistinctVals::new(arr.clone()).process(&queries), expected);
+        assert_eq!(DistinctVals::new(arr).process_hilbert(&queries), expected);
+    }
+
+    #[test]
+    fn test_mos_algorithm_with_updates() {
+        let mut rng = SmallRng::new(9);
+        let n = 50;
+        let mut arr = (0..n).map(|_| rng.next_u64() as usize % 10).collect::<Vec<_>>();
+        let init = arr.clone();
+        let mut updates = vec![];
+        let mut queries = vec![];
+        let mut expected = vec![];
+        for _ in 0..300 {
+            if rng.next_u64() % 2 == 0 {
+                let pos = rng.next_u64() as usize % n;
+                let new = rng.next_u64() as usize % 15;
+                updates.push((pos, arr[pos], new));
+                arr[pos] = new;
+            } else {
+                let (l, r, _) = random_queries(&mut rng, n, 1)[0];
+                queries.push((l, r, updates.len(), ()));
+                expected.push(distinct(&arr[l..=r]));
+            }
+        }
+
+        let answers = DistinctVals::new(init).process_with_updates(&updates, &queries);
+        assert_eq!(answers, expected);
+    }
+
+    #[test]
+    fn test_mos_algorithm_on_tree() {
+        let mut rng = SmallRng::new(10);
+        let n = 60;
+        let mut adj = vec![vec![]; n];
+        let mut parent = vec![n; n];
+        for v in 1..n {
+            let p = rng.next_u64() as usize % v;
+            parent[v] = p;
+            adj[p].push(v);
+            adj[v].push(p);
+        }
+        let color = (0..n).map(|_| rng.next_u64() as usize % 8).collect::<Vec<_>>();
+        let queries = random_queries(&mut rng, n, 200)
+            .into_iter()
+            .map(|(u, v, _)| if rng.next_u64() % 2 == 0 { (u, v, ()) } else { (v, u, ()) })
+            .collect::<Vec<_>>();
+
+        let path = |mut u: usize, mut v: usize| {
+            let depth = |mut x: usize| {
+                let mut d = 0;
+                while x != 0 {
+                    x = parent[x];
+                    d += 1;
+                }
+                d
+            };
+            let mut vertices = vec![];
+            while depth(u) > depth(v) {
+                vertices.push(u);
+                u = parent[u];
+            }
+            while depth(v) > depth(u) {
+                vertices.push(v);
+                v = parent[v];
+            }
+            while u != v {
+                vertices.push(u);
+                vertices.push(v);
+                u = parent[u];
+                v = parent[v];
+            }
+            vertices.push(u);
+            vertices
+        };
+        let expected = queries
+            .iter()
+            .map(|&(u, v, _)| distinct(&path(u, v).iter().map(|&w| color[w]).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        let answers = process_tree(&mut DistinctVals::new(color), &adj, 0, &queries);
+        assert_eq!(answers, expected);
+    }
 
     #[test]
     fn test_mos_algorithm() {
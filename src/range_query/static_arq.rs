@@ -51,7 +51,7 @@ impl<T: ArqSpec> StaticArq<T> {
 
     fn push(&mut self, p: usize) {
         if let Some(ref f) = self.app[p].take() {
-            let s = ((self.app.len() + p - 1) / p / 2).next_power_of_two() as i64;
+            let s = (((self.app.len() + p - 1) / p).next_power_of_two() / 2) as i64;
             self.apply(p << 1, f, s);
             self.apply(p << 1 | 1, f, s);
         }
@@ -136,6 +136,101 @@ impl<T: ArqSpec> StaticArq<T> {
         T::op(&l_agg, &r_agg)
     }
 
+    /// Returns the nodes covering the entries from l to r, inclusive, in
+    /// left-to-right order, pushing down any updates pending above them.
+    fn cover(&mut self, mut l: usize, mut r: usize) -> Vec<usize> {
+        l += self.app.len();
+        r += self.app.len();
+        if l < r {
+            self.push_to(l);
+        }
+        self.push_to(r);
+        let (mut l_nodes, mut r_nodes) = (vec![], vec![]);
+        while l <= r {
+            if l & 1 == 1 {
+                l_nodes.push(l);
+                l += 1;
+            }
+            if r & 1 == 0 {
+                r_nodes.push(r);
+                r -= 1;
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        l_nodes.extend(r_nodes.into_iter().rev());
+        l_nodes
+    }
+
+    /// Binary searches for the largest r such that pred(a_l + ... + a_{r-1})
+    /// holds, where pred(identity) must hold. If pred is monotone, i.e., once
+    /// false it remains false as r grows, r is simply the first position at
+    /// which pred fails, or size if it never does. The order of op is
+    /// preserved, so non-commutative monoids are fine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if l > size.
+    pub fn max_right(&mut self, l: usize, pred: impl Fn(&T::S) -> bool) -> usize {
+        let size = self.app.len();
+        assert!(l <= size, "Index out of bound. length:{}, but l:{}.", size, l);
+        if l == size {
+            return size;
+        }
+        let mut agg = T::identity();
+        for mut p in self.cover(l, size - 1) {
+            let next = T::op(&agg, &self.val[p]);
+            if pred(&next) {
+                agg = next;
+                continue;
+            }
+            while p < size {
+                self.push(p);
+                p <<= 1;
+                let next = T::op(&agg, &self.val[p]);
+                if pred(&next) {
+                    agg = next;
+                    p |= 1;
+                }
+            }
+            return p - size;
+        }
+        size
+    }
+
+    /// Binary searches for the smallest l such that pred(a_l + ... + a_{r-1})
+    /// holds, where pred(identity) must hold. This mirrors max_right().
+    ///
+    /// # Panics
+    ///
+    /// Panics if r > size.
+    pub fn min_left(&mut self, r: usize, pred: impl Fn(&T::S) -> bool) -> usize {
+        let size = self.app.len();
+        assert!(r <= size, "Index out of bound. length:{}, but r:{}.", size, r);
+        if r == 0 {
+            return 0;
+        }
+        let mut agg = T::identity();
+        for mut p in self.cover(0, r - 1).into_iter().rev() {
+            let next = T::op(&self.val[p], &agg);
+            if pred(&next) {
+                agg = next;
+                continue;
+            }
+            while p < size {
+                self.push(p);
+                p = p << 1 | 1;
+                let next = T::op(&self.val[p], &agg);
+                if pred(&next) {
+                    agg = next;
+                    p ^= 1;
+                }
+            }
+            return p + 1 - size;
+        }
+        0
+    }
+
     /// For debug
     pub fn debug(self: &Self) -> &[T::S] {
         return &self.val[self.app.len()..];
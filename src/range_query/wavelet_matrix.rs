@@ -0,0 +1,272 @@
+//! Wavelet Matrix for static range k-th smallest and range frequency queries
+use crate::collection::{BitArray, RankSelect};
+use std::collections::BinaryHeap;
+
+/// A static sequence of non-negative integers supporting, in O(log σ) time
+/// where σ is the maximum value, queries such as:
+///
+/// - the k-th smallest value among a[l..r]
+/// - the number of values in [lower, upper) among a[l..r]
+/// - the number of occurrences of a value among a[0..r]
+///
+/// Ranges are half-open. Values can be compressed with order::SparseIndex
+/// beforehand to keep σ small.
+pub struct WaveletMatrix {
+    levels: Vec<RankSelect>,
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl WaveletMatrix {
+    /// Builds the matrix in O(n log σ) time.
+    pub fn new(vals: &[u64]) -> Self {
+        let max = vals.iter().copied().max().unwrap_or(0);
+        let height = (64 - max.leading_zeros() as usize).max(1);
+        let mut cur = vals.to_vec();
+        let mut levels = Vec::with_capacity(height);
+        let mut zeros = Vec::with_capacity(height);
+        for b in (0..height).rev() {
+            let mut bits = BitArray::new(vals.len());
+            for (i, &v) in cur.iter().enumerate() {
+                if v >> b & 1 == 1 {
+                    bits.set(i);
+                }
+            }
+            let (lo, hi): (Vec<u64>, Vec<u64>) = cur.iter().partition(|&&v| v >> b & 1 == 0);
+            zeros.push(lo.len());
+            levels.push(RankSelect::from(bits));
+            cur = lo;
+            cur.extend(hi);
+        }
+        Self { levels, zeros, len: vals.len() }
+    }
+
+    /// Gets the number of values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn height(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Follows the range [l, r) of a level down to the next level, on the side
+    // given by bit.
+    fn descend(&self, d: usize, l: usize, r: usize, bit: bool) -> (usize, usize) {
+        let level = &self.levels[d];
+        if bit {
+            (self.zeros[d] + level.rank1(l), self.zeros[d] + level.rank1(r))
+        } else {
+            (level.rank0(l), level.rank0(r))
+        }
+    }
+
+    fn check_range(&self, l: usize, r: usize) {
+        if l > r || r > self.len {
+            panic!("Invalid range. length:{}, but l:{}, r:{}.", self.len, l, r);
+        }
+    }
+
+    /// Gets the value at the specified index.
+    pub fn access(&self, mut idx: usize) -> u64 {
+        if idx >= self.len {
+            panic!("Index out of bound. length:{}, but idx:{}.", self.len, idx);
+        }
+        let mut val = 0;
+        for (d, level) in self.levels.iter().enumerate() {
+            let bit = level.bits().test(idx);
+            val = val << 1 | bit as u64;
+            idx = if bit { self.zeros[d] + level.rank1(idx) } else { level.rank0(idx) };
+        }
+        val
+    }
+
+    /// Counts the occurrences of val among a[0..r].
+    pub fn rank(&self, val: u64, r: usize) -> usize {
+        self.check_range(0, r);
+        if self.height() < 64 && val >> self.height() != 0 {
+            return 0;
+        }
+        let (mut l, mut r) = (0, r);
+        for d in 0..self.height() {
+            let bit = val >> (self.height() - 1 - d) & 1 == 1;
+            let next = self.descend(d, l, r, bit);
+            l = next.0;
+            r = next.1;
+        }
+        r - l
+    }
+
+    /// Returns the k-th smallest value (0-indexed) among a[l..r], or None if k >= r - l.
+    pub fn kth_smallest(&self, l: usize, r: usize, mut k: usize) -> Option<u64> {
+        self.check_range(l, r);
+        if k >= r - l {
+            return None;
+        }
+        let (mut l, mut r) = (l, r);
+        let mut val = 0;
+        for d in 0..self.height() {
+            let num_zeros = self.levels[d].rank0(r) - self.levels[d].rank0(l);
+            let bit = k >= num_zeros;
+            if bit {
+                k -= num_zeros;
+            }
+            val = val << 1 | bit as u64;
+            let next = self.descend(d, l, r, bit);
+            l = next.0;
+            r = next.1;
+        }
+        Some(val)
+    }
+
+    /// Returns the k-th largest value (0-indexed) among a[l..r], or None if k >= r - l.
+    pub fn kth_largest(&self, l: usize, r: usize, k: usize) -> Option<u64> {
+        self.check_range(l, r);
+        if k >= r - l {
+            return None;
+        }
+        self.kth_smallest(l, r, r - l - 1 - k)
+    }
+
+    /// Counts the values less than upper among a[l..r].
+    pub fn count_less(&self, l: usize, r: usize, upper: u64) -> usize {
+        self.check_range(l, r);
+        if self.height() < 64 && upper >> self.height() != 0 {
+            return r - l;
+        }
+        let (mut l, mut r) = (l, r);
+        let mut cnt = 0;
+        for d in 0..self.height() {
+            let bit = upper >> (self.height() - 1 - d) & 1 == 1;
+            if bit {
+                cnt += self.levels[d].rank0(r) - self.levels[d].rank0(l);
+            }
+            let next = self.descend(d, l, r, bit);
+            l = next.0;
+            r = next.1;
+        }
+        cnt
+    }
+
+    /// Counts the values in [lower, upper) among a[l..r].
+    pub fn range_freq(&self, l: usize, r: usize, lower: u64, upper: u64) -> usize {
+        if lower >= upper {
+            self.check_range(l, r);
+            return 0;
+        }
+        self.count_less(l, r, upper) - self.count_less(l, r, lower)
+    }
+
+    /// Returns the largest value less than upper among a[l..r], if any.
+    pub fn prev_value(&self, l: usize, r: usize, upper: u64) -> Option<u64> {
+        match self.count_less(l, r, upper) {
+            0 => None,
+            cnt => self.kth_smallest(l, r, cnt - 1),
+        }
+    }
+
+    /// Returns the smallest value not less than lower among a[l..r], if any.
+    pub fn next_value(&self, l: usize, r: usize, lower: u64) -> Option<u64> {
+        let cnt = self.count_less(l, r, lower);
+        self.kth_smallest(l, r, cnt)
+    }
+
+    /// Returns up to k pairs of (value, occurrences) among a[l..r], the most
+    /// frequent first; ties are broken by smaller values. Runs in
+    /// O(k log k log σ) time.
+    pub fn top_k(&self, l: usize, r: usize, k: usize) -> Vec<(u64, usize)> {
+        use std::cmp::Reverse;
+        self.check_range(l, r);
+        let mut res = Vec::with_capacity(k);
+        // (count, smallest value in the subtree, depth, l, r)
+        let mut heap = BinaryHeap::new();
+        if l < r {
+            heap.push((r - l, Reverse(0), 0, l, r));
+        }
+        while let Some((cnt, Reverse(val), d, l, r)) = heap.pop() {
+            if res.len() == k {
+                break;
+            }
+            if d == self.height() {
+                res.push((val, cnt));
+                continue;
+            }
+            let b = self.height() - 1 - d;
+            for &bit in &[false, true] {
+                let (nl, nr) = self.descend(d, l, r, bit);
+                if nl < nr {
+                    heap.push((nr - nl, Reverse(val | (bit as u64) << b), d + 1, nl, nr));
+                }
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::SmallRng;
+
+    #[test]
+    fn test_wavelet_matrix() {
+        let a = [5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 8, 0];
+        let wm = WaveletMatrix::new(&a);
+
+        assert_eq!(wm.access(7), 6);
+        assert_eq!(wm.rank(5, 7), 4);
+        assert_eq!(wm.rank(9, 12), 0);
+        assert_eq!(wm.kth_smallest(1, 5, 0), Some(2));
+        assert_eq!(wm.kth_largest(1, 5, 0), Some(5));
+        assert_eq!(wm.kth_smallest(1, 5, 4), None);
+        assert_eq!(wm.range_freq(0, 12, 2, 6), 7);
+        assert_eq!(wm.prev_value(0, 4, 5), Some(4));
+        assert_eq!(wm.next_value(4, 9, 3), Some(5));
+        assert_eq!(wm.next_value(4, 9, 7), None);
+        assert_eq!(wm.top_k(0, 12, 2), vec![(5, 4), (1, 2)]);
+    }
+
+    #[test]
+    fn test_wavelet_matrix_random() {
+        let mut rng = SmallRng::new(6);
+        for &sigma in &[1, 2, 7, 100, u64::MAX] {
+            let n = 60;
+            let a = (0..n).map(|_| rng.next_u64() % sigma).collect::<Vec<_>>();
+            let wm = WaveletMatrix::new(&a);
+            for (i, &v) in a.iter().enumerate() {
+                assert_eq!(wm.access(i), v);
+                assert_eq!(wm.rank(v, i), a[..i].iter().filter(|&&x| x == v).count());
+            }
+            for _ in 0..200 {
+                let x = rng.next_u64() as usize % (n + 1);
+                let y = rng.next_u64() as usize % (n + 1);
+                let (l, r) = (x.min(y), x.max(y));
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort_unstable();
+                for k in 0..=sorted.len() {
+                    assert_eq!(wm.kth_smallest(l, r, k), sorted.get(k).copied());
+                }
+                let lower = rng.next_u64() % sigma;
+                let upper = rng.next_u64() % sigma;
+                let in_range = sorted.iter().filter(|&&v| lower <= v && v < upper).count();
+                assert_eq!(wm.range_freq(l, r, lower, upper), in_range);
+                assert_eq!(wm.prev_value(l, r, upper), sorted.iter().rev().find(|&&v| v < upper).copied());
+                assert_eq!(wm.next_value(l, r, lower), sorted.iter().find(|&&v| v >= lower).copied());
+
+                let mut freq = std::collections::BTreeMap::new();
+                for &v in &sorted {
+                    *freq.entry(v).or_insert(0) += 1;
+                }
+                let mut expected = freq.into_iter().collect::<Vec<_>>();
+                expected.sort_by_key(|&(v, c)| (std::cmp::Reverse(c), v));
+                expected.truncate(3);
+                assert_eq!(wm.top_k(l, r, 3), expected);
+            }
+        }
+    }
+}